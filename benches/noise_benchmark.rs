@@ -19,6 +19,7 @@ fn simulate_fractal_noise(n: u32) -> PathEl {
 			wavelength: 1.0,
 			sample_distance: 2.0,
 			offset: 5.0,
+			..Default::default()
 		})
 	}
 	for line in lines {
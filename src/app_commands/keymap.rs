@@ -0,0 +1,233 @@
+use std::fmt::Display;
+
+use druid::{KeyEvent, Selector};
+
+use super::{find_command, COMMANDS};
+use crate::config::{self, ConfigFile};
+
+const KEYMAP_FILE: &str = "keymap.conf";
+// Written in place of keys to leave a command without any
+const UNBOUND: &str = "none";
+
+/// A key along with the modifiers that must be held for it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyBinding {
+	pub ctrl: bool,
+	pub shift: bool,
+	pub alt: bool,
+	pub meta: bool,
+	// The name of a physical key as druid::Code displays it, eg. KeyA, Digit1, ArrowLeft
+	pub code: String,
+}
+
+impl KeyBinding {
+	/// Parse a binding such as `Ctrl+Shift+Z`, `Shift+1` or `ArrowLeft`
+	pub fn parse(text: &str) -> Result<Self, String> {
+		let mut binding = Self {
+			ctrl: false,
+			shift: false,
+			alt: false,
+			meta: false,
+			code: String::new(),
+		};
+		// Split on '+' but allow the key itself to be written as '+'
+		let (mods, key) = match text.trim().rsplit_once('+') {
+			Some((mods, "")) => (mods.strip_suffix('+').unwrap_or(mods), "+"),
+			Some((mods, key)) => (mods, key),
+			None => ("", text.trim()),
+		};
+		for modifier in mods.split('+').map(str::trim).filter(|m| !m.is_empty()) {
+			match modifier.to_lowercase().as_str() {
+				"ctrl" | "control" => binding.ctrl = true,
+				"shift" => binding.shift = true,
+				"alt" | "option" => binding.alt = true,
+				"meta" | "cmd" | "super" => binding.meta = true,
+				other => return Err(format!("Unknown modifier '{}' in '{}'", other, text)),
+			}
+		}
+		binding.code =
+			Self::code_name(key.trim()).ok_or_else(|| format!("Missing key in '{}'", text))?;
+		Ok(binding)
+	}
+
+	// Expand the short names people are likely to write into druid::Code names
+	fn code_name(key: &str) -> Option<String> {
+		let mut chars = key.chars();
+		let code = match (chars.next(), chars.next()) {
+			(None, _) => return None,
+			(Some(c), None) if c.is_ascii_alphabetic() => format!("Key{}", c.to_ascii_uppercase()),
+			(Some(c), None) if c.is_ascii_digit() => format!("Digit{}", c),
+			(Some('='), None) => "Equal".to_string(),
+			(Some('+'), None) => "Equal".to_string(),
+			(Some('-'), None) => "Minus".to_string(),
+			(Some(','), None) => "Comma".to_string(),
+			(Some('.'), None) => "Period".to_string(),
+			(Some('/'), None) => "Slash".to_string(),
+			(Some('\''), None) => "Quote".to_string(),
+			(Some(';'), None) => "Semicolon".to_string(),
+			(Some('['), None) => "BracketLeft".to_string(),
+			(Some(']'), None) => "BracketRight".to_string(),
+			_ => match key.to_lowercase().as_str() {
+				"esc" => "Escape".to_string(),
+				"del" => "Delete".to_string(),
+				"return" => "Enter".to_string(),
+				"left" => "ArrowLeft".to_string(),
+				"right" => "ArrowRight".to_string(),
+				"up" => "ArrowUp".to_string(),
+				"down" => "ArrowDown".to_string(),
+				_ => key.to_string(),
+			},
+		};
+		Some(code)
+	}
+
+	pub fn matches(&self, event: &KeyEvent) -> bool {
+		event.mods.ctrl() == self.ctrl
+			&& event.mods.shift() == self.shift
+			&& event.mods.alt() == self.alt
+			&& event.mods.meta() == self.meta
+			&& event.code.to_string() == self.code
+	}
+}
+
+impl Display for KeyBinding {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for (held, name) in [
+			(self.ctrl, "Ctrl+"),
+			(self.alt, "Alt+"),
+			(self.shift, "Shift+"),
+			(self.meta, "Meta+"),
+		] {
+			if held {
+				f.write_str(name)?;
+			}
+		}
+		let code = self
+			.code
+			.strip_prefix("Key")
+			.or_else(|| self.code.strip_prefix("Digit"))
+			.unwrap_or(&self.code);
+		f.write_str(code)
+	}
+}
+
+/// Maps key presses to the commands they trigger
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keymap {
+	bindings: Vec<(KeyBinding, Selector)>,
+}
+
+impl Keymap {
+	/// The keymap built from each command's default keys
+	pub fn builtin() -> Self {
+		let mut bindings = Vec::new();
+		for command in COMMANDS {
+			for key in command.default_keys {
+				match KeyBinding::parse(key) {
+					Ok(binding) => bindings.push((binding, command.selector)),
+					Err(e) => log::error!("Bad default binding for {}: {}", command.id, e),
+				}
+			}
+		}
+		Self { bindings }
+	}
+
+	/// Load the user's keymap, overriding the builtin bindings of any command it mentions.
+	///
+	/// If the user doesn't have a keymap yet, one listing the defaults is written for them to edit.
+	pub fn load() -> Self {
+		let mut keymap = Self::builtin();
+		let file = match config::read(KEYMAP_FILE) {
+			Some(file) => file,
+			None => {
+				if let Err(e) = config::write_text(KEYMAP_FILE, &keymap.to_text()) {
+					log::warn!("Could not write default keymap: {}", e);
+				}
+				return keymap;
+			}
+		};
+		keymap.apply(&file);
+		keymap
+	}
+
+	// Replace the bindings for each command listed in the file.
+	//
+	// An empty value keeps the command's default keys, so that keys given to commands in later
+	// versions still reach older keymaps, and UNBOUND removes them.
+	fn apply(&mut self, file: &ConfigFile) {
+		for (_, entries) in &file.sections {
+			for (id, keys) in entries {
+				if keys.trim().is_empty() {
+					continue;
+				}
+				let command = match find_command(id) {
+					Some(command) => command,
+					None => {
+						log::warn!("Unknown command '{}' in keymap", id);
+						continue;
+					}
+				};
+				self.bindings
+					.retain(|(_, selector)| *selector != command.selector);
+				if keys.trim().eq_ignore_ascii_case(UNBOUND) {
+					continue;
+				}
+				for key in keys.split(',').map(str::trim).filter(|k| !k.is_empty()) {
+					match KeyBinding::parse(key) {
+						Ok(binding) => self.bindings.push((binding, command.selector)),
+						Err(e) => log::warn!("Bad binding for {}: {}", id, e),
+					}
+				}
+			}
+		}
+	}
+
+	fn to_text(&self) -> String {
+		let mut text = String::from(
+			"# Lipuma keymap\n# Each line binds a command to a comma separated list of keys, eg.\n# edit.redo = Ctrl+Shift+Z, Ctrl+Y\n# Commands that aren't listed keep their default keys, and none unbinds a command\n\n",
+		);
+		// Commands without keys are left out, so that they pick up any default keys added later
+		for command in COMMANDS {
+			let keys: Vec<String> = self
+				.keys_for(command.selector)
+				.iter()
+				.map(|k| k.to_string())
+				.collect();
+			if keys.is_empty() {
+				continue;
+			}
+			text.push_str(&format!("{} = {}\n", command.id, keys.join(", ")));
+		}
+		text
+	}
+
+	pub fn lookup(&self, event: &KeyEvent) -> Option<Selector> {
+		self.bindings
+			.iter()
+			.find(|(binding, _)| binding.matches(event))
+			.map(|(_, selector)| *selector)
+	}
+
+	pub fn keys_for(&self, selector: Selector) -> Vec<&KeyBinding> {
+		self.bindings
+			.iter()
+			.filter(|(_, s)| *s == selector)
+			.map(|(binding, _)| binding)
+			.collect()
+	}
+
+	/// The keys bound to a command, formatted for display
+	pub fn describe(&self, selector: Selector) -> String {
+		self.keys_for(selector)
+			.iter()
+			.map(|k| k.to_string())
+			.collect::<Vec<_>>()
+			.join(", ")
+	}
+}
+
+impl Default for Keymap {
+	fn default() -> Self {
+		Self::builtin()
+	}
+}
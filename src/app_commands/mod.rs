@@ -0,0 +1,266 @@
+//! Every action the user can perform, addressable by name and bindable to keys.
+//!
+//! Actions are plain druid commands, so they can be submitted from buttons,
+//! the command palette or the keymap alike and are all handled in one place.
+pub mod keymap;
+
+use druid::Selector;
+
+pub use keymap::{KeyBinding, Keymap};
+
+pub const SELECT_FRACTAL_LINE_TOOL: Selector = Selector::new("lipuma.select-fractal-line-tool");
+pub const SELECT_SELECTION_TOOL: Selector = Selector::new("lipuma.select-selection-tool");
+pub const SELECT_EDIT_TOOL: Selector = Selector::new("lipuma.select-edit-tool");
+/// Every command that switches tools, these can be held to switch temporarily
+pub const TOOL_COMMANDS: &[Selector] = &[
+	SELECT_FRACTAL_LINE_TOOL,
+	SELECT_SELECTION_TOOL,
+	SELECT_EDIT_TOOL,
+];
+
+pub const DELETE_SELECTED: Selector = Selector::new("lipuma.delete-selected");
+pub const SELECT_ALL: Selector = Selector::new("lipuma.select-all");
+pub const DESELECT_ALL: Selector = Selector::new("lipuma.deselect-all");
+pub const UNDO: Selector = Selector::new("lipuma.undo");
+pub const REDO: Selector = Selector::new("lipuma.redo");
+
+/// Bake the selected fractal lines into fixed paths, as they look at the current time
+pub const CONVERT_TO_PATH: Selector = Selector::new("lipuma.convert-to-path");
+pub const UNION_SELECTED: Selector = Selector::new("lipuma.union-selected");
+pub const INTERSECT_SELECTED: Selector = Selector::new("lipuma.intersect-selected");
+/// Cut every other selected shape out of the bottommost selected shape
+pub const SUBTRACT_SELECTED: Selector = Selector::new("lipuma.subtract-selected");
+
+pub const ZOOM_IN: Selector = Selector::new("lipuma.zoom-in");
+pub const ZOOM_OUT: Selector = Selector::new("lipuma.zoom-out");
+pub const PAN_LEFT: Selector = Selector::new("lipuma.pan-left");
+pub const PAN_RIGHT: Selector = Selector::new("lipuma.pan-right");
+pub const PAN_UP: Selector = Selector::new("lipuma.pan-up");
+pub const PAN_DOWN: Selector = Selector::new("lipuma.pan-down");
+/// Zoom and center the view so that every object is visible
+pub const ZOOM_TO_FIT: Selector = Selector::new("lipuma.zoom-to-fit");
+/// Zoom and center the view on the selected objects
+pub const ZOOM_TO_SELECTION: Selector = Selector::new("lipuma.zoom-to-selection");
+/// Show the canvas at 1:1 scale, keeping the center of the view in place
+pub const ZOOM_TO_ACTUAL_SIZE: Selector = Selector::new("lipuma.zoom-to-actual-size");
+/// Undo any rotation of the view, keeping the center of the view in place
+pub const RESET_ROTATION: Selector = Selector::new("lipuma.reset-rotation");
+pub const TOGGLE_GRID: Selector = Selector::new("lipuma.toggle-grid");
+pub const TOGGLE_RULERS: Selector = Selector::new("lipuma.toggle-rulers");
+pub const TOGGLE_ORIGIN: Selector = Selector::new("lipuma.toggle-origin");
+pub const TOGGLE_GRID_SNAPPING: Selector = Selector::new("lipuma.toggle-grid-snapping");
+
+pub const TOGGLE_ANIMATION: Selector = Selector::new("lipuma.toggle-animation");
+pub const SHOW_EXPORT_ANIMATION: Selector = Selector::new("lipuma.show-export-animation");
+
+pub const SHOW_COMMAND_PALETTE: Selector = Selector::new("lipuma.show-command-palette");
+pub const SHOW_PREFERENCES: Selector = Selector::new("lipuma.show-preferences");
+
+/// A user facing action in the registry
+pub struct AppCommand {
+	// Stable name used to refer to the command in the keymap file
+	pub id: &'static str,
+	// Human readable name shown in the command palette
+	pub name: &'static str,
+	pub selector: Selector,
+	pub default_keys: &'static [&'static str],
+}
+
+impl AppCommand {
+	/// Whether every word of a search query appears in the command's name or id
+	pub fn matches(&self, query: &str) -> bool {
+		let name = self.name.to_lowercase();
+		query
+			.to_lowercase()
+			.split_whitespace()
+			.all(|word| name.contains(word) || self.id.contains(word))
+	}
+}
+
+pub const COMMANDS: &[AppCommand] = &[
+	AppCommand {
+		id: "tool.fractal-line",
+		name: "Fractal Line Tool",
+		selector: SELECT_FRACTAL_LINE_TOOL,
+		default_keys: &["L"],
+	},
+	AppCommand {
+		id: "tool.selection",
+		name: "Selection Tool",
+		selector: SELECT_SELECTION_TOOL,
+		default_keys: &["V"],
+	},
+	AppCommand {
+		id: "tool.edit",
+		name: "Edit Tool",
+		selector: SELECT_EDIT_TOOL,
+		default_keys: &["N"],
+	},
+	AppCommand {
+		id: "edit.delete",
+		name: "Delete Selected",
+		selector: DELETE_SELECTED,
+		default_keys: &["Backspace", "Delete"],
+	},
+	AppCommand {
+		id: "edit.select-all",
+		name: "Select All",
+		selector: SELECT_ALL,
+		default_keys: &["Ctrl+A"],
+	},
+	AppCommand {
+		id: "edit.deselect-all",
+		name: "Deselect All",
+		selector: DESELECT_ALL,
+		default_keys: &["Escape"],
+	},
+	AppCommand {
+		id: "edit.undo",
+		name: "Undo",
+		selector: UNDO,
+		default_keys: &["Ctrl+Z"],
+	},
+	AppCommand {
+		id: "edit.redo",
+		name: "Redo",
+		selector: REDO,
+		default_keys: &["Ctrl+Shift+Z", "Ctrl+Y"],
+	},
+	AppCommand {
+		id: "path.convert",
+		name: "Convert to Path",
+		selector: CONVERT_TO_PATH,
+		default_keys: &["Ctrl+Shift+C"],
+	},
+	AppCommand {
+		id: "path.union",
+		name: "Union",
+		selector: UNION_SELECTED,
+		default_keys: &["Ctrl+Shift+U"],
+	},
+	AppCommand {
+		id: "path.intersect",
+		name: "Intersection",
+		selector: INTERSECT_SELECTED,
+		default_keys: &["Ctrl+Shift+I"],
+	},
+	AppCommand {
+		id: "path.subtract",
+		name: "Difference",
+		selector: SUBTRACT_SELECTED,
+		default_keys: &["Ctrl+Shift+D"],
+	},
+	AppCommand {
+		id: "view.zoom-in",
+		name: "Zoom In",
+		selector: ZOOM_IN,
+		default_keys: &["=", "Shift+=", "NumpadAdd"],
+	},
+	AppCommand {
+		id: "view.zoom-out",
+		name: "Zoom Out",
+		selector: ZOOM_OUT,
+		default_keys: &["-", "NumpadSubtract"],
+	},
+	AppCommand {
+		id: "view.pan-left",
+		name: "Pan Left",
+		selector: PAN_LEFT,
+		default_keys: &["ArrowLeft"],
+	},
+	AppCommand {
+		id: "view.pan-right",
+		name: "Pan Right",
+		selector: PAN_RIGHT,
+		default_keys: &["ArrowRight"],
+	},
+	AppCommand {
+		id: "view.pan-up",
+		name: "Pan Up",
+		selector: PAN_UP,
+		default_keys: &["ArrowUp"],
+	},
+	AppCommand {
+		id: "view.pan-down",
+		name: "Pan Down",
+		selector: PAN_DOWN,
+		default_keys: &["ArrowDown"],
+	},
+	AppCommand {
+		id: "view.zoom-to-fit",
+		name: "Zoom to Fit",
+		selector: ZOOM_TO_FIT,
+		default_keys: &["Shift+1"],
+	},
+	AppCommand {
+		id: "view.zoom-to-selection",
+		name: "Zoom to Selection",
+		selector: ZOOM_TO_SELECTION,
+		default_keys: &["Shift+2"],
+	},
+	AppCommand {
+		id: "view.actual-size",
+		name: "Zoom to 100%",
+		selector: ZOOM_TO_ACTUAL_SIZE,
+		default_keys: &["Shift+0"],
+	},
+	AppCommand {
+		id: "view.reset-rotation",
+		name: "Reset Rotation",
+		selector: RESET_ROTATION,
+		default_keys: &["Shift+R"],
+	},
+	AppCommand {
+		id: "view.toggle-grid",
+		name: "Toggle Grid",
+		selector: TOGGLE_GRID,
+		default_keys: &["Ctrl+Quote"],
+	},
+	AppCommand {
+		id: "view.toggle-rulers",
+		name: "Toggle Rulers",
+		selector: TOGGLE_RULERS,
+		default_keys: &["Ctrl+R"],
+	},
+	AppCommand {
+		id: "view.toggle-origin",
+		name: "Toggle Origin Marker",
+		selector: TOGGLE_ORIGIN,
+		default_keys: &[],
+	},
+	AppCommand {
+		id: "snap.toggle-grid",
+		name: "Toggle Grid Snapping",
+		selector: TOGGLE_GRID_SNAPPING,
+		default_keys: &["Shift+Quote"],
+	},
+	AppCommand {
+		id: "animation.toggle",
+		name: "Play/Pause Animation",
+		selector: TOGGLE_ANIMATION,
+		default_keys: &["Ctrl+Space"],
+	},
+	AppCommand {
+		id: "animation.export",
+		name: "Export Animation…",
+		selector: SHOW_EXPORT_ANIMATION,
+		default_keys: &["Ctrl+E"],
+	},
+	AppCommand {
+		id: "app.command-palette",
+		name: "Command Palette",
+		selector: SHOW_COMMAND_PALETTE,
+		default_keys: &["Ctrl+Shift+P"],
+	},
+	AppCommand {
+		id: "app.preferences",
+		name: "Preferences",
+		selector: SHOW_PREFERENCES,
+		default_keys: &["Ctrl+Comma"],
+	},
+];
+
+/// Look up a command by its keymap id
+pub fn find_command(id: &str) -> Option<&'static AppCommand> {
+	COMMANDS.iter().find(|command| command.id == id)
+}
@@ -0,0 +1,134 @@
+//! Reading and writing of the small text files lipuma keeps in the user's config directory.
+//!
+//! Files are made of `key = value` lines, optionally grouped under `[section]`
+//! headers. Lines starting with `#` are comments, and section names escape `[`, `]`, `\` and
+//! line breaks with a backslash.
+use std::{fs, io, path::PathBuf};
+
+/// A parsed config file, entries that appear before any header belong to the section ""
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConfigFile {
+	pub sections: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl ConfigFile {
+	pub fn parse(text: &str) -> Self {
+		let mut file = Self::default();
+		for line in text.lines().map(str::trim) {
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+				file.sections
+					.push((unescape_section(name.trim()), Vec::new()));
+			} else if let Some((key, value)) = line.split_once('=') {
+				file.entries_mut("")
+					.push((key.trim().to_string(), value.trim().to_string()));
+			} else {
+				log::warn!("Ignoring malformed config line: {}", line);
+			}
+		}
+		file
+	}
+
+	// The entries of the last section, which is where new lines are added while parsing
+	fn entries_mut(&mut self, default_section: &str) -> &mut Vec<(String, String)> {
+		if self.sections.is_empty() {
+			self.sections
+				.push((default_section.to_string(), Vec::new()));
+		}
+		&mut self.sections.last_mut().unwrap().1
+	}
+
+	pub fn section(&self, name: &str) -> Option<&[(String, String)]> {
+		self.sections
+			.iter()
+			.find(|(section, _)| section == name)
+			.map(|(_, entries)| entries.as_slice())
+	}
+
+	pub fn add_section(&mut self, name: &str, entries: Vec<(String, String)>) {
+		self.sections.push((name.to_string(), entries));
+	}
+
+	pub fn to_text(&self) -> String {
+		let mut text = String::new();
+		for (name, entries) in &self.sections {
+			if !name.is_empty() {
+				text.push_str(&format!("[{}]\n", escape_section(name)));
+			}
+			for (key, value) in entries {
+				text.push_str(&format!("{} = {}\n", key, value));
+			}
+			text.push('\n');
+		}
+		text
+	}
+}
+
+// Section names can be typed in by the user, so anything that would end the header early or
+// start a new line is escaped with a backslash
+fn escape_section(name: &str) -> String {
+	let mut escaped = String::new();
+	for c in name.chars() {
+		match c {
+			'\\' => escaped.push_str("\\\\"),
+			'[' => escaped.push_str("\\["),
+			']' => escaped.push_str("\\]"),
+			'\n' => escaped.push_str("\\n"),
+			'\r' => escaped.push_str("\\r"),
+			c => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+fn unescape_section(name: &str) -> String {
+	let mut unescaped = String::new();
+	let mut chars = name.chars();
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			unescaped.push(c);
+			continue;
+		}
+		match chars.next() {
+			Some('n') => unescaped.push('\n'),
+			Some('r') => unescaped.push('\r'),
+			Some(escaped) => unescaped.push(escaped),
+			None => unescaped.push('\\'),
+		}
+	}
+	unescaped
+}
+
+/// The path of a file within lipuma's config directory
+pub fn config_path(file: &str) -> Option<PathBuf> {
+	dirs::config_dir().map(|dir| dir.join("lipuma").join(file))
+}
+
+/// Read and parse a config file, returning None if it doesn't exist or can't be read
+pub fn read(file: &str) -> Option<ConfigFile> {
+	let path = config_path(file)?;
+	match fs::read_to_string(&path) {
+		Ok(text) => Some(ConfigFile::parse(&text)),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+		Err(e) => {
+			log::warn!("Failed to read {}: {}", path.display(), e);
+			None
+		}
+	}
+}
+
+/// Write raw text to a config file, creating the config directory if needed
+pub fn write_text(file: &str, text: &str) -> io::Result<()> {
+	let path = config_path(file)
+		.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No config directory"))?;
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir)?;
+	}
+	fs::write(path, text)
+}
+
+pub fn write(file: &str, config: &ConfigFile) -> io::Result<()> {
+	write_text(file, &config.to_text())
+}
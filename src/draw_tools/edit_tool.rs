@@ -0,0 +1,199 @@
+use druid::im::Vector;
+use druid::widget::{Flex, Label};
+use druid::{Affine, Color, Data, Event, Lens, Point, Rect, RenderContext, Size, Widget};
+
+use super::snapping::Snapper;
+use super::tool::{Tool, ToolCursor};
+use crate::preferences::COLLISION_TOLERANCE;
+use crate::render_objects::{ObjectId, RenderObject};
+
+// Size in screen pixels of the square drawn at each handle
+const HANDLE_SIZE: f64 = 8.0;
+// Distance in screen pixels within which a handle or object can be grabbed
+const GRAB_RADIUS: f64 = 6.0;
+
+#[derive(Data, Debug, Clone, Copy, PartialEq, Eq)]
+enum EditState {
+	Standby,
+	// Dragging a handle, given by the id of its object and the index of the handle
+	Dragging(ObjectId, usize),
+}
+
+/// Reshapes existing objects by dragging their handles, such as the ends of a line
+#[derive(Data, Debug, Clone, Copy, PartialEq, Lens)]
+pub struct EditTool {
+	state: EditState,
+}
+
+impl EditTool {
+	pub fn new() -> Self {
+		Self {
+			state: EditState::Standby,
+		}
+	}
+
+	// The handles of every selected object in canvas space, with the object id and handle index
+	fn handles(data: &Vector<RenderObject>) -> impl Iterator<Item = (ObjectId, usize, Point)> + '_ {
+		data.iter()
+			.filter(|object| object.is_selected())
+			.flat_map(|object| {
+				object
+					.drawable
+					.handles()
+					.into_iter()
+					.enumerate()
+					.map(move |(h, point)| (object.id, h, object.transform * point))
+			})
+	}
+
+	fn handle_at(
+		data: &Vector<RenderObject>,
+		pos: Point,
+		radius: f64,
+	) -> Option<(ObjectId, usize)> {
+		Self::handles(data)
+			.filter(|(_, _, point)| point.distance(pos) <= radius)
+			.min_by(|a, b| {
+				a.2.distance(pos)
+					.partial_cmp(&b.2.distance(pos))
+					.unwrap_or(std::cmp::Ordering::Equal)
+			})
+			.map(|(i, h, _)| (i, h))
+	}
+
+	fn handle_rect(point: Point) -> Rect {
+		Rect::from_center_size(point, Size::new(HANDLE_SIZE, HANDLE_SIZE))
+	}
+
+	pub fn get_configuration() -> impl Widget<Self> {
+		Flex::column()
+			.with_child(Label::new("Edit Tool"))
+			.with_child(Label::new("Click a line to select it, then drag its ends"))
+	}
+}
+
+impl Tool for EditTool {
+	fn enable(&mut self, _data: &mut Vector<RenderObject>) {
+		self.state = EditState::Standby;
+	}
+
+	fn disable(&mut self, _data: &mut Vector<RenderObject>) {
+		self.state = EditState::Standby;
+	}
+
+	fn event(
+		&mut self,
+		event: &druid::Event,
+		ctx: &mut druid::EventCtx,
+		data: &mut Vector<RenderObject>,
+		snapper: &mut Snapper,
+		env: &druid::Env,
+	) {
+		let radius = GRAB_RADIUS * snapper.pixel_size();
+		let tolerance = env.get(COLLISION_TOLERANCE);
+		match event {
+			Event::MouseDown(e) => {
+				if let Some((object, handle)) = Self::handle_at(data, e.pos, radius) {
+					self.state = EditState::Dragging(object, handle);
+				} else {
+					// Clicking selects the object under the pointer, shift adds it to the selection
+					let hit = data
+						.iter()
+						.rev()
+						.find(|object| object.hit_test(e.pos, radius, tolerance, snapper.time()))
+						.map(|object| object.id);
+					for object in data.iter_mut() {
+						if Some(object.id) == hit {
+							object.select();
+						} else if !e.mods.shift() {
+							object.deselect();
+						}
+					}
+				}
+				ctx.set_handled();
+			}
+			Event::MouseMove(e) => {
+				if let EditState::Dragging(id, handle) = self.state {
+					// The handle would otherwise keep snapping back to where it was
+					snapper.ignore(id);
+					let pos = snapper.snap(e.pos, None, false);
+					if let Some(object) = data.iter_mut().find(|object| object.id == id) {
+						let local = object.transform.inverse() * pos;
+						object.drawable.move_handle(handle, local);
+					}
+					ctx.set_handled();
+				}
+			}
+			Event::MouseUp(_) => {
+				if let EditState::Dragging(..) = self.state {
+					self.state = EditState::Standby;
+					ctx.set_handled();
+				}
+			}
+			_ => (),
+		}
+	}
+
+	fn get_preview(&self) -> Option<RenderObject> {
+		None
+	}
+
+	fn cursor(
+		&self,
+		pos: Point,
+		objects: &Vector<RenderObject>,
+		pixel_size: f64,
+		_time: f64,
+		_env: &druid::Env,
+	) -> ToolCursor {
+		let radius = GRAB_RADIUS * pixel_size;
+		let handle = Self::handle_at(objects, pos, radius).and_then(|(id, handle)| {
+			let object = objects.iter().find(|object| object.id == id)?;
+			Some((object, object.drawable.handles()[handle]))
+		});
+		match handle {
+			// Resize along whichever axis the handle sits furthest out from the object's middle on
+			Some((object, handle)) => {
+				let center = object.bounds().center();
+				let outward = object.transform * handle - center;
+				if outward.x.abs() >= outward.y.abs() {
+					ToolCursor::ResizeLeftRight
+				} else {
+					ToolCursor::ResizeUpDown
+				}
+			}
+			// Clicking an object only selects it, which its hover outline already shows
+			None => ToolCursor::Arrow,
+		}
+	}
+
+	fn paint_overlay(
+		&self,
+		ctx: &mut druid::PaintCtx,
+		to_widget: Affine,
+		objects: &Vector<RenderObject>,
+	) {
+		let color = Color::rgb8(0, 120, 215);
+		for (i, h, point) in Self::handles(objects) {
+			let rect = Self::handle_rect(to_widget * point);
+			if self.state == EditState::Dragging(i, h) {
+				ctx.fill(rect, &color);
+			} else {
+				ctx.fill(rect, &Color::WHITE);
+			}
+			ctx.stroke(rect, &color, 1.0);
+		}
+	}
+
+	fn overlay_rects(&self, to_widget: Affine, objects: &Vector<RenderObject>) -> Vec<Rect> {
+		Self::handles(objects)
+			.map(|(_, _, point)| Self::handle_rect(to_widget * point).inflate(1.0, 1.0))
+			.collect()
+	}
+}
+
+impl Default for EditTool {
+	fn default() -> Self {
+		Self::new()
+	}
+}
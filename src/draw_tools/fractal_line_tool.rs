@@ -1,13 +1,25 @@
-use druid::{im::Vector, widget::*, Data, Lens, LensExt, Point, Widget};
+use druid::{im::Vector, widget::*, Data, Lens, LensExt, Point, Widget, WidgetExt};
 use rand::random;
 use std::default::Default;
 
 use crate::{
-	render_objects::{fractal_line::FractalNoise, Drawable, FractalLine, RenderObject},
-	widgets::compose_widgets::{integer_stepper, slider_with_label},
+	noise::{NoiseObj, OctaveStyle},
+	render_objects::{
+		animation::Animation,
+		fractal_line::{FractalNoise, OctaveWeights, MAX_OCTAVES},
+		multi_strand_line::{MultiStrandLine, Strands},
+		stroke::{Stroke, StrokeMode, WidthSource},
+		taper::{Taper, TaperKind},
+		Drawable, FractalLine, RenderObject,
+	},
+	widgets::{compose_widgets::Param, curve_editor::CurveEditor, tool_preview::LineSwatch},
 };
 
-use super::tool::Tool;
+use super::{
+	presets::LinePreset,
+	snapping::Snapper,
+	tool::{Tool, ToolCursor},
+};
 
 #[derive(Data, Clone, Copy, PartialEq, Eq, Debug)]
 enum ToolState {
@@ -24,7 +36,16 @@ pub struct FractalLineTool {
 	default_sample_distance: f64,
 	default_offset: f64,
 	default_laurancity: f64,
+	default_lacunarity: f64,
+	default_amplitude: f64,
 	default_octaves: i8,
+	default_weights: OctaveWeights,
+	default_noise: NoiseObj,
+	default_style: OctaveStyle,
+	default_taper: Taper,
+	default_stroke: Stroke,
+	default_strands: Strands,
+	default_animation: Animation,
 }
 
 impl FractalLineTool {
@@ -44,21 +65,127 @@ impl FractalLineTool {
 			default_offset: 5.0,
 			default_octaves: 3,
 			default_laurancity: 0.35,
+			default_lacunarity: 2.0,
+			default_amplitude: 3.0,
+			default_weights: OctaveWeights::default(),
+			default_noise: NoiseObj::default(),
+			default_style: OctaveStyle::default(),
+			default_taper: Taper::default(),
+			default_stroke: Stroke::default(),
+			default_strands: Strands::default(),
+			default_animation: Animation::default(),
+		}
+	}
+
+	/// The current settings saved under a name
+	pub fn preset(&self, name: &str) -> LinePreset {
+		LinePreset {
+			name: name.to_string(),
+			width: self.default_width,
+			wavelength: self.wavelength,
+			sample_distance: self.default_sample_distance,
+			offset: self.default_offset,
+			laurancity: self.default_laurancity,
+			lacunarity: self.default_lacunarity,
+			amplitude: self.default_amplitude,
+			octaves: self.default_octaves,
+			weights: self.default_weights,
+			noise: self.default_noise,
+			style: self.default_style,
+			taper: self.default_taper,
+			stroke: self.default_stroke,
+			strands: self.default_strands,
+			animation: self.default_animation,
+		}
+	}
+
+	pub fn apply_preset(&mut self, preset: &LinePreset) {
+		self.default_width = preset.width;
+		self.wavelength = preset.wavelength;
+		self.default_sample_distance = preset.sample_distance;
+		self.default_offset = preset.offset;
+		self.default_laurancity = preset.laurancity;
+		self.default_lacunarity = preset.lacunarity;
+		self.default_amplitude = preset.amplitude;
+		self.default_octaves = preset.octaves;
+		self.default_weights = preset.weights;
+		self.default_noise = preset.noise;
+		self.default_style = preset.style;
+		self.default_taper = preset.taper;
+		self.default_stroke = preset.stroke;
+		self.default_strands = preset.strands;
+		self.default_animation = preset.animation;
+	}
+
+	/// A line between two points using the tool's current settings
+	pub fn build_line(&self, start: Point, end: Point, seed: u32) -> FractalLine {
+		FractalLine {
+			start,
+			end,
+			noise: FractalNoise {
+				algorithm: self.default_noise,
+				style: self.default_style,
+				lacunarity: self.default_lacunarity,
+				amplitude: self.default_amplitude,
+				weights: self.default_weights,
+				..FractalNoise::new(seed, self.default_laurancity, self.default_octaves)
+			},
+			width: self.default_width,
+			wavelength: self.wavelength,
+			sample_distance: self.default_sample_distance,
+			offset: self.default_offset,
+			taper: self.default_taper,
+			stroke: self.default_stroke,
+			animation: self.default_animation,
+		}
+	}
+
+	/// Wrap a line as the tool would draw it, splitting it into strands if configured to
+	pub fn build_object(&self, line: FractalLine) -> RenderObject {
+		if self.default_strands.count > 1 {
+			RenderObject::new(Drawable::MultiStrandLine(MultiStrandLine::new(
+				line,
+				self.default_strands,
+			)))
+		} else {
+			RenderObject::new(Drawable::FractalLine(line))
 		}
 	}
 
+	/// Give an existing object the tool's current settings, keeping its id, position and seed.
+	///
+	/// Returns None for objects the tool didn't draw.
+	pub fn restyle(&self, object: &RenderObject) -> Option<RenderObject> {
+		let line = match &object.drawable {
+			Drawable::FractalLine(line) => line,
+			Drawable::MultiStrandLine(multi) => &multi.line,
+			_ => return None,
+		};
+		Some(RenderObject {
+			id: object.id,
+			transform: object.transform,
+			selected: object.selected,
+			..self.build_object(self.build_line(line.start, line.end, line.noise.seed()))
+		})
+	}
+
 	fn on_mouse_move(
 		&mut self,
 		event: &druid::MouseEvent,
 		ctx: &mut druid::EventCtx,
 		_data: &mut Vector<RenderObject>,
+		snapper: &mut Snapper,
 	) {
 		match self.state {
 			ToolState::Drawing => {
 				ctx.set_handled();
-				self.preview.end = event.pos;
+				self.preview.end =
+					snapper.snap(event.pos, Some(self.preview.start), event.mods.shift());
+			}
+			ToolState::Standby => {
+				// Snap anyway so that the target we would start from is shown
+				snapper.snap(event.pos, None, false);
 			}
-			ToolState::Standby => (),
 		}
 	}
 
@@ -67,17 +194,11 @@ impl FractalLineTool {
 		event: &druid::MouseEvent,
 		ctx: &mut druid::EventCtx,
 		_data: &mut Vector<RenderObject>,
+		snapper: &mut Snapper,
 	) {
+		let pos = snapper.snap(event.pos, None, false);
 		self.state = ToolState::Drawing;
-		self.preview = FractalLine {
-			start: event.pos,
-			end: event.pos,
-			noise: FractalNoise::new(random(), self.default_laurancity, self.default_octaves),
-			width: self.default_width,
-			wavelength: self.wavelength,
-			sample_distance: self.default_sample_distance,
-			offset: self.default_offset,
-		};
+		self.preview = self.build_line(pos, pos, random());
 		ctx.set_handled();
 	}
 
@@ -87,10 +208,12 @@ impl FractalLineTool {
 		ctx: &mut druid::EventCtx,
 
 		data: &mut Vector<RenderObject>,
+		snapper: &mut Snapper,
 	) {
 		match self.state {
 			ToolState::Drawing => {
-				self.preview.end = event.pos;
+				self.preview.end =
+					snapper.snap(event.pos, Some(self.preview.start), event.mods.shift());
 				let obj = self.get_preview().unwrap();
 				self.state = ToolState::Standby;
 				data.push_back(obj);
@@ -100,19 +223,179 @@ impl FractalLineTool {
 		}
 	}
 
+	fn octave_weights() -> impl Widget<OctaveWeights> {
+		let weight = LineParams::new().weight;
+		let mut col = Flex::column().with_child(Label::new("Octave Weights"));
+		for octave in 0..MAX_OCTAVES {
+			col.add_child(
+				weight
+					.clone()
+					.named(format!("Octave {}", octave + 2))
+					.slider(druid::lens::Map::new(
+						move |w: &OctaveWeights| w.0[octave],
+						move |w: &mut OctaveWeights, new| w.0[octave] = new,
+					)),
+			);
+		}
+		col
+	}
+
+	fn taper_settings() -> impl Widget<Taper> {
+		let params = LineParams::new();
+		Flex::column()
+			.with_child(Label::new("Taper"))
+			.with_child(RadioGroup::new(TaperKind::variants()).lens(Taper::kind))
+			.with_child(params.taper_exponent.slider(Taper::exponent))
+			.with_child(params.taper_start.slider(Taper::start))
+			.with_child(params.taper_end.slider(Taper::end))
+			.with_child(CurveEditor::new().lens(Taper::curve))
+	}
+
+	fn stroke_settings() -> impl Widget<Stroke> {
+		let params = LineParams::new();
+		Flex::column()
+			.with_child(Label::new("Stroke"))
+			.with_child(RadioGroup::new(StrokeMode::variants()).lens(Stroke::mode))
+			.with_child(params.stroke_width.slider(Stroke::width))
+			.with_child(RadioGroup::new(WidthSource::variants()).lens(Stroke::source))
+			.with_child(params.variation.slider(Stroke::variation))
+			.with_child(Self::taper_settings().lens(Stroke::pressure))
+			.with_child(CurveEditor::new().lens(Stroke::curve))
+	}
+
+	fn strand_settings() -> impl Widget<Strands> {
+		let params = LineParams::new();
+		Flex::column()
+			.with_child(Label::new("Strands"))
+			.with_child(params.strand_count.stepper(Strands::count))
+			.with_child(params.spacing.slider(Strands::spacing))
+			.with_child(params.offset_step.slider(Strands::offset_step))
+			.with_child(Checkbox::new("Vary seed").lens(Strands::vary_seed))
+			.with_child(params.fade.slider(Strands::fade))
+	}
+
+	fn animation_settings() -> impl Widget<Animation> {
+		let params = LineParams::new();
+		Flex::column()
+			.with_child(Label::new("Animation"))
+			.with_child(params.crawl_speed.slider(Animation::crawl_speed))
+			.with_child(params.shimmer_speed.slider(Animation::shimmer_speed))
+	}
+
 	pub fn get_configuration() -> impl Widget<Self> {
+		let params = LineParams::new();
 		Flex::column()
 			.with_child(Label::new("Fractal Line Tool"))
-			.with_child(slider_with_label(0.0, 10.0, Self::default_width))
-			.with_child(slider_with_label(1.0, 10.0, Self::wavelength))
-			.with_child(slider_with_label(0.1, 10.0, Self::default_sample_distance))
-			.with_child(integer_stepper(
-				0,
-				5,
-				Self::default_octaves.map(|v| *v as i32, |v, new| *v = new as i8),
-			))
-			.with_child(slider_with_label(0.0, 10.0, Self::default_offset))
-			.with_child(slider_with_label(0.0, 0.5, Self::default_laurancity))
+			.with_child(LineSwatch::new())
+			.with_child(params.width.slider(Self::default_width))
+			.with_child(params.wavelength.slider(Self::wavelength))
+			.with_child(params.sample_distance.slider(Self::default_sample_distance))
+			.with_child(
+				params
+					.octaves
+					.stepper(Self::default_octaves.map(|v| *v as i32, |v, new| *v = new as i8)),
+			)
+			.with_child(params.offset.slider(Self::default_offset))
+			.with_child(params.laurancity.slider(Self::default_laurancity))
+			.with_child(params.lacunarity.slider(Self::default_lacunarity))
+			.with_child(params.amplitude.slider(Self::default_amplitude))
+			.with_child(Self::octave_weights().lens(Self::default_weights))
+			.with_child(RadioGroup::new(NoiseObj::variants()).lens(Self::default_noise))
+			.with_child(RadioGroup::new(OctaveStyle::variants()).lens(Self::default_style))
+			.with_child(Self::taper_settings().lens(Self::default_taper))
+			.with_child(Self::stroke_settings().lens(Self::default_stroke))
+			.with_child(Self::strand_settings().lens(Self::default_strands))
+			.with_child(Self::animation_settings().lens(Self::default_animation))
+	}
+}
+
+/// Ranges and defaults of the tool's numeric settings, shared by its sliders and by presets read
+/// from disk so that a preset can't hold a value the sliders wouldn't allow
+pub(super) struct LineParams {
+	pub width: Param,
+	pub wavelength: Param,
+	pub sample_distance: Param,
+	pub octaves: Param,
+	pub offset: Param,
+	pub laurancity: Param,
+	pub lacunarity: Param,
+	pub amplitude: Param,
+	pub weight: Param,
+	pub taper_exponent: Param,
+	pub taper_start: Param,
+	pub taper_end: Param,
+	pub stroke_width: Param,
+	pub variation: Param,
+	pub strand_count: Param,
+	pub spacing: Param,
+	pub offset_step: Param,
+	pub fade: Param,
+	pub crawl_speed: Param,
+	pub shimmer_speed: Param,
+}
+
+impl LineParams {
+	pub fn new() -> Self {
+		let tool = FractalLineTool::new();
+		let taper = Taper::default();
+		let stroke = Stroke::default();
+		let strands = Strands::default();
+		Self {
+			width: Param::new("Width", 0.0, 10.0, tool.default_width)
+				.unit("px")
+				.tooltip("How far the line strays from a straight path"),
+			wavelength: Param::new("Wavelength", 1.0, 10.0, tool.wavelength)
+				.unit("px")
+				.tooltip("Length of line covered by one cycle of the first octave"),
+			sample_distance: Param::new("Sample Distance", 0.1, 10.0, tool.default_sample_distance)
+				.unit("px")
+				.log_scale()
+				.tooltip("Spacing of the points the line is drawn through, must be above zero"),
+			octaves: Param::new(
+				"Octaves",
+				0.0,
+				(MAX_OCTAVES + 1) as f64,
+				tool.default_octaves as f64,
+			)
+			.tooltip("Number of layers of increasingly fine noise"),
+			offset: Param::new("Offset", 0.0, 10.0, tool.default_offset)
+				.tooltip("Where along the noise the line starts sampling"),
+			laurancity: Param::new("Laurancity", 0.0, 0.5, tool.default_laurancity)
+				.tooltip("How much each octave contributes compared to the one before"),
+			lacunarity: Param::new("Lacunarity", 1.0, 4.0, tool.default_lacunarity)
+				.tooltip("How much the frequency grows with each octave"),
+			amplitude: Param::new("Amplitude", 0.0, 10.0, tool.default_amplitude)
+				.tooltip("Overall scale of the noise"),
+			weight: Param::new("Octave Weight", 0.0, 2.0, 1.0)
+				.tooltip("Extra scaling of this octave on top of the laurancity falloff"),
+			taper_exponent: Param::new("Exponent", 1.0, 32.0, taper.exponent)
+				.log_scale()
+				.tooltip(
+					"For the power taper, higher values keep the line rough closer to its ends",
+				),
+			taper_start: Param::new("Start", 0.0, 0.5, taper.start)
+				.tooltip("For the asymmetric taper, the fraction of the line spent tapering in"),
+			taper_end: Param::new("End", 0.0, 0.5, taper.end)
+				.tooltip("For the asymmetric taper, the fraction of the line spent tapering out"),
+			stroke_width: Param::new("Stroke Width", 0.1, 20.0, stroke.width)
+				.unit("px")
+				.log_scale(),
+			variation: Param::new("Variation", 0.0, 2.0, stroke.variation)
+				.tooltip("How strongly the noise width source changes the width"),
+			strand_count: Param::new("Count", 1.0, 16.0, strands.count as f64)
+				.tooltip("Number of parallel copies of the line"),
+			spacing: Param::new("Spacing", 0.0, 50.0, strands.spacing).unit("px"),
+			offset_step: Param::new("Offset Step", 0.0, 10.0, strands.offset_step)
+				.tooltip("How much further along the noise each strand samples"),
+			fade: Param::new("Fade", 0.0, 1.0, strands.fade)
+				.tooltip("How much fainter the outer strands are drawn"),
+			crawl_speed: Param::new("Crawl Speed", -10.0, 10.0, 0.0)
+				.unit("/s")
+				.tooltip("How fast the noise slides along the line"),
+			shimmer_speed: Param::new("Shimmer Speed", 0.0, 5.0, 0.0)
+				.unit("Hz")
+				.tooltip("How often the noise is reseeded, blending between seeds"),
+		}
 	}
 }
 
@@ -136,18 +419,31 @@ impl Tool for FractalLineTool {
 		event: &druid::Event,
 		ctx: &mut druid::EventCtx,
 		data: &mut Vector<RenderObject>,
+		snapper: &mut Snapper,
+		_env: &druid::Env,
 	) {
 		match event {
-			druid::Event::MouseDown(event) => self.on_mouse_down(event, ctx, data),
-			druid::Event::MouseUp(event) => self.on_mouse_up(event, ctx, data),
-			druid::Event::MouseMove(event) => self.on_mouse_move(event, ctx, data),
+			druid::Event::MouseDown(event) => self.on_mouse_down(event, ctx, data, snapper),
+			druid::Event::MouseUp(event) => self.on_mouse_up(event, ctx, data, snapper),
+			druid::Event::MouseMove(event) => self.on_mouse_move(event, ctx, data, snapper),
 			_ => (),
 		}
 	}
 
+	fn cursor(
+		&self,
+		_pos: Point,
+		_objects: &Vector<RenderObject>,
+		_pixel_size: f64,
+		_time: f64,
+		_env: &druid::Env,
+	) -> ToolCursor {
+		ToolCursor::Crosshair
+	}
+
 	fn get_preview(&self) -> Option<RenderObject> {
 		match self.state {
-			ToolState::Drawing => Some(RenderObject::new(Drawable::FractalLine(self.preview))),
+			ToolState::Drawing => Some(self.build_object(self.preview)),
 			ToolState::Standby => None,
 		}
 	}
@@ -1,7 +1,11 @@
+mod edit_tool;
 mod fractal_line_tool;
+pub mod presets;
 mod selection_tool;
+pub mod snapping;
 pub mod tool;
 
+pub use edit_tool::EditTool;
 pub use fractal_line_tool::FractalLineTool;
 pub use selection_tool::SelectionTool;
 pub use tool::ToolObj as Tool;
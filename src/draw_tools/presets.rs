@@ -0,0 +1,392 @@
+use druid::{im::Vector, Data, Lens, Selector};
+
+use super::{fractal_line_tool::LineParams, FractalLineTool};
+use crate::{
+	config::{self, ConfigFile},
+	noise::{NoiseObj, OctaveStyle},
+	render_objects::{
+		animation::Animation,
+		curve::Curve,
+		fractal_line::OctaveWeights,
+		multi_strand_line::Strands,
+		stroke::{Stroke, StrokeMode, WidthSource},
+		taper::{Taper, TaperKind},
+	},
+	widgets::compose_widgets::Param,
+};
+
+const PRESETS_FILE: &str = "presets.conf";
+
+/// Apply the named preset to the fractal line tool
+pub const APPLY_PRESET: Selector<String> = Selector::new("lipuma.apply-preset");
+/// Save the fractal line tool's current settings as a preset
+pub const SAVE_PRESET: Selector = Selector::new("lipuma.save-preset");
+pub const DELETE_PRESET: Selector<String> = Selector::new("lipuma.delete-preset");
+
+/// A named set of fractal line tool settings
+#[derive(Data, Clone, Debug, PartialEq, Lens)]
+pub struct LinePreset {
+	pub name: String,
+	pub width: f64,
+	pub wavelength: f64,
+	pub sample_distance: f64,
+	pub offset: f64,
+	pub laurancity: f64,
+	pub lacunarity: f64,
+	pub amplitude: f64,
+	pub octaves: i8,
+	pub weights: OctaveWeights,
+	pub noise: NoiseObj,
+	pub style: OctaveStyle,
+	pub taper: Taper,
+	pub stroke: Stroke,
+	pub strands: Strands,
+	pub animation: Animation,
+}
+
+impl LinePreset {
+	fn to_entries(&self) -> Vec<(String, String)> {
+		let mut entries = vec![
+			("width".to_string(), self.width.to_string()),
+			("wavelength".to_string(), self.wavelength.to_string()),
+			(
+				"sample_distance".to_string(),
+				self.sample_distance.to_string(),
+			),
+			("offset".to_string(), self.offset.to_string()),
+			("laurancity".to_string(), self.laurancity.to_string()),
+			("lacunarity".to_string(), self.lacunarity.to_string()),
+			("amplitude".to_string(), self.amplitude.to_string()),
+			("octaves".to_string(), self.octaves.to_string()),
+			("weights".to_string(), number_list(&self.weights.0)),
+			(
+				"noise".to_string(),
+				variant_name(NoiseObj::variants(), &self.noise),
+			),
+			(
+				"style".to_string(),
+				variant_name(OctaveStyle::variants(), &self.style),
+			),
+		];
+		entries.extend(taper_entries("taper", &self.taper));
+		entries.extend(vec![
+			(
+				"stroke.mode".to_string(),
+				variant_name(StrokeMode::variants(), &self.stroke.mode),
+			),
+			("stroke.width".to_string(), self.stroke.width.to_string()),
+			(
+				"stroke.source".to_string(),
+				variant_name(WidthSource::variants(), &self.stroke.source),
+			),
+			(
+				"stroke.variation".to_string(),
+				self.stroke.variation.to_string(),
+			),
+			(
+				"stroke.curve".to_string(),
+				number_list(&self.stroke.curve.0),
+			),
+		]);
+		entries.extend(taper_entries("stroke.pressure", &self.stroke.pressure));
+		entries.extend(vec![
+			("strands.count".to_string(), self.strands.count.to_string()),
+			(
+				"strands.spacing".to_string(),
+				self.strands.spacing.to_string(),
+			),
+			(
+				"strands.offset_step".to_string(),
+				self.strands.offset_step.to_string(),
+			),
+			(
+				"strands.vary_seed".to_string(),
+				self.strands.vary_seed.to_string(),
+			),
+			("strands.fade".to_string(), self.strands.fade.to_string()),
+			(
+				"animation.crawl_speed".to_string(),
+				self.animation.crawl_speed.to_string(),
+			),
+			(
+				"animation.shimmer_speed".to_string(),
+				self.animation.shimmer_speed.to_string(),
+			),
+		]);
+		entries
+	}
+
+	// Settings missing from the file keep the tool's defaults, and values outside what the
+	// tool's sliders allow are clamped into range as they could otherwise hang drawing
+	fn from_entries(name: &str, entries: &[(String, String)]) -> Self {
+		let params = LineParams::new();
+		let mut preset = Self {
+			name: name.to_string(),
+			..Self::default()
+		};
+		for (key, value) in entries {
+			let parsed = match key.as_str() {
+				"width" => number(value, &params.width).map(|v| preset.width = v),
+				"wavelength" => number(value, &params.wavelength).map(|v| preset.wavelength = v),
+				"sample_distance" => {
+					number(value, &params.sample_distance).map(|v| preset.sample_distance = v)
+				}
+				"offset" => number(value, &params.offset).map(|v| preset.offset = v),
+				"laurancity" => number(value, &params.laurancity).map(|v| preset.laurancity = v),
+				"lacunarity" => number(value, &params.lacunarity).map(|v| preset.lacunarity = v),
+				"amplitude" => number(value, &params.amplitude).map(|v| preset.amplitude = v),
+				"octaves" => {
+					number(value, &params.octaves).map(|v| preset.octaves = v.round() as i8)
+				}
+				"weights" => {
+					numbers(value, &params.weight).map(|v| preset.weights = OctaveWeights(v))
+				}
+				"noise" => variant(NoiseObj::variants(), value).map(|v| preset.noise = v),
+				"style" => variant(OctaveStyle::variants(), value).map(|v| preset.style = v),
+				"stroke.mode" => {
+					variant(StrokeMode::variants(), value).map(|v| preset.stroke.mode = v)
+				}
+				"stroke.width" => {
+					number(value, &params.stroke_width).map(|v| preset.stroke.width = v)
+				}
+				"stroke.source" => {
+					variant(WidthSource::variants(), value).map(|v| preset.stroke.source = v)
+				}
+				"stroke.variation" => {
+					number(value, &params.variation).map(|v| preset.stroke.variation = v)
+				}
+				"stroke.curve" => curve(value).map(|v| preset.stroke.curve = v),
+				"strands.count" => number(value, &params.strand_count)
+					.map(|v| preset.strands.count = v.round() as i32),
+				"strands.spacing" => {
+					number(value, &params.spacing).map(|v| preset.strands.spacing = v)
+				}
+				"strands.offset_step" => {
+					number(value, &params.offset_step).map(|v| preset.strands.offset_step = v)
+				}
+				"strands.vary_seed" => value.parse().ok().map(|v| preset.strands.vary_seed = v),
+				"strands.fade" => number(value, &params.fade).map(|v| preset.strands.fade = v),
+				"animation.crawl_speed" => {
+					number(value, &params.crawl_speed).map(|v| preset.animation.crawl_speed = v)
+				}
+				"animation.shimmer_speed" => {
+					number(value, &params.shimmer_speed).map(|v| preset.animation.shimmer_speed = v)
+				}
+				key => {
+					if let Some(setting) = key.strip_prefix("taper.") {
+						set_taper(&mut preset.taper, setting, value, &params)
+					} else if let Some(setting) = key.strip_prefix("stroke.pressure.") {
+						set_taper(&mut preset.stroke.pressure, setting, value, &params)
+					} else {
+						log::warn!("Unknown setting '{}' in preset {}", key, name);
+						Some(())
+					}
+				}
+			};
+			if parsed.is_none() {
+				log::warn!("Bad value '{}' for {} in preset {}", value, key, name);
+			}
+		}
+		preset
+	}
+
+	// The presets offered before the user has saved any of their own
+	fn builtin() -> Vector<Self> {
+		Vector::from(vec![
+			Self {
+				name: "coastline".to_string(),
+				width: 8.0,
+				wavelength: 8.0,
+				sample_distance: 2.0,
+				offset: 5.0,
+				laurancity: 0.45,
+				lacunarity: 2.0,
+				amplitude: 3.0,
+				octaves: 6,
+				..Self::default()
+			},
+			Self {
+				name: "lightning".to_string(),
+				width: 4.0,
+				wavelength: 3.0,
+				sample_distance: 1.0,
+				offset: 0.0,
+				laurancity: 0.25,
+				lacunarity: 2.5,
+				amplitude: 5.0,
+				octaves: 4,
+				..Self::default()
+			},
+			Self {
+				name: "rough pencil".to_string(),
+				width: 0.6,
+				wavelength: 2.0,
+				sample_distance: 0.5,
+				offset: 5.0,
+				laurancity: 0.4,
+				lacunarity: 2.0,
+				amplitude: 1.0,
+				octaves: 3,
+				..Self::default()
+			},
+		])
+	}
+}
+
+fn taper_entries(prefix: &str, taper: &Taper) -> Vec<(String, String)> {
+	vec![
+		(
+			format!("{}.kind", prefix),
+			variant_name(TaperKind::variants(), &taper.kind),
+		),
+		(format!("{}.exponent", prefix), taper.exponent.to_string()),
+		(format!("{}.start", prefix), taper.start.to_string()),
+		(format!("{}.end", prefix), taper.end.to_string()),
+		(format!("{}.curve", prefix), number_list(&taper.curve.0)),
+	]
+}
+
+// Set one of a taper's settings, None if the setting is unknown or its value is bad
+fn set_taper(taper: &mut Taper, setting: &str, value: &str, params: &LineParams) -> Option<()> {
+	match setting {
+		"kind" => variant(TaperKind::variants(), value).map(|v| taper.kind = v),
+		"exponent" => number(value, &params.taper_exponent).map(|v| taper.exponent = v),
+		"start" => number(value, &params.taper_start).map(|v| taper.start = v),
+		"end" => number(value, &params.taper_end).map(|v| taper.end = v),
+		"curve" => curve(value).map(|v| taper.curve = v),
+		_ => None,
+	}
+}
+
+fn number(value: &str, param: &Param) -> Option<f64> {
+	value.parse().ok().map(|v| param.clamp(v))
+}
+
+// A comma separated list of exactly N numbers, each clamped into the parameter's range
+fn numbers<const N: usize>(value: &str, param: &Param) -> Option<[f64; N]> {
+	let values = value
+		.split(',')
+		.map(|v| number(v.trim(), param))
+		.collect::<Option<Vec<f64>>>()?;
+	values.try_into().ok()
+}
+
+// Curves are kept within [0, 1] like the curve editor does
+fn curve(value: &str) -> Option<Curve> {
+	numbers(value, &Param::new("Curve", 0.0, 1.0, 1.0)).map(Curve)
+}
+
+fn number_list(values: &[f64]) -> String {
+	values
+		.iter()
+		.map(|v| v.to_string())
+		.collect::<Vec<_>>()
+		.join(", ")
+}
+
+// Enums are saved under the names their radio buttons show
+fn variant_name<T: PartialEq>(variants: Vec<(&'static str, T)>, value: &T) -> String {
+	variants
+		.into_iter()
+		.find(|(_, variant)| variant == value)
+		.map_or_else(String::new, |(name, _)| name.to_string())
+}
+
+fn variant<T>(variants: Vec<(&'static str, T)>, name: &str) -> Option<T> {
+	variants
+		.into_iter()
+		.find(|(variant, _)| *variant == name)
+		.map(|(_, variant)| variant)
+}
+
+impl Default for LinePreset {
+	fn default() -> Self {
+		FractalLineTool::new().preset("default")
+	}
+}
+
+/// The user's saved presets along with the one last applied
+#[derive(Data, Clone, Debug, PartialEq, Lens)]
+pub struct PresetLibrary {
+	pub presets: Vector<LinePreset>,
+	pub active: Option<String>,
+	// Name typed in for the next preset to save
+	pub new_name: String,
+}
+
+impl PresetLibrary {
+	/// Load the presets file, falling back to the builtin presets if there isn't one
+	pub fn load() -> Self {
+		let file = match config::read(PRESETS_FILE) {
+			Some(file) => file,
+			None => {
+				return Self {
+					presets: LinePreset::builtin(),
+					..Self::default()
+				}
+			}
+		};
+		let mut library = Self::default();
+		for (name, entries) in &file.sections {
+			if name.is_empty() {
+				library.active = entries
+					.iter()
+					.find(|(key, _)| key == "active")
+					.map(|(_, value)| value.clone());
+			} else {
+				library
+					.presets
+					.push_back(LinePreset::from_entries(name, entries));
+			}
+		}
+		library
+	}
+
+	pub fn save(&self) {
+		let mut file = ConfigFile::default();
+		if let Some(active) = &self.active {
+			file.add_section("", vec![("active".to_string(), active.clone())]);
+		}
+		for preset in &self.presets {
+			file.add_section(&preset.name, preset.to_entries());
+		}
+		if let Err(e) = config::write(PRESETS_FILE, &file) {
+			log::error!("Failed to save presets: {}", e);
+		}
+	}
+
+	pub fn get(&self, name: &str) -> Option<&LinePreset> {
+		self.presets.iter().find(|preset| preset.name == name)
+	}
+
+	pub fn active_preset(&self) -> Option<&LinePreset> {
+		self.active.as_ref().and_then(|name| self.get(name))
+	}
+
+	/// Add a preset, replacing any existing preset with the same name
+	pub fn insert(&mut self, preset: LinePreset) {
+		match self.presets.iter().position(|p| p.name == preset.name) {
+			Some(index) => {
+				self.presets.set(index, preset);
+			}
+			None => self.presets.push_back(preset),
+		}
+	}
+
+	pub fn remove(&mut self, name: &str) {
+		self.presets.retain(|preset| preset.name != name);
+		if self.active.as_deref() == Some(name) {
+			self.active = None;
+		}
+	}
+}
+
+impl Default for PresetLibrary {
+	fn default() -> Self {
+		Self {
+			presets: Vector::new(),
+			active: None,
+			new_name: String::new(),
+		}
+	}
+}
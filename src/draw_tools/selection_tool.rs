@@ -1,12 +1,17 @@
-use super::tool::Tool;
+use super::snapping::Snapper;
+use super::tool::{Tool, ToolCursor};
 use druid::im::Vector;
 use druid::kurbo::Shape;
 use druid::widget::{Flex, Label};
 use druid::{Data, Event, Lens, Point, Rect, Widget};
 
+use crate::preferences::COLLISION_TOLERANCE;
 use crate::render_objects::drawable::DrawableObj;
 use crate::render_objects::{selection_rect::SelectionRect, RenderObject};
 
+// Distance in screen pixels from a selected object within which the move cursor is shown
+const GRAB_RADIUS: f64 = 6.0;
+
 #[derive(Data, Debug, Clone, Copy, PartialEq, Eq)]
 enum SelectionState {
 	Selecting,
@@ -29,11 +34,11 @@ impl SelectionTool {
 		}
 	}
 
-	fn update_selected(&self, data: &mut Vector<RenderObject>) {
+	fn update_selected(&self, data: &mut Vector<RenderObject>, tolerance: f64, time: f64) {
 		let bound = Rect::from_points(self.start_coord, self.end_coord);
 		'outer: for item in data.iter_mut() {
-			if !bound.intersect(item.drawable.AABB()).is_empty() {
-				for segment in item.drawable.fine_collision_shape(5.0).segments() {
+			if !bound.intersect(item.bounds()).is_empty() {
+				for segment in item.collision_shape(tolerance, time).segments() {
 					match segment {
 						druid::kurbo::PathSeg::Line(l) => {
 							for check in bound.path_segments(0.01) {
@@ -56,6 +61,20 @@ impl SelectionTool {
 		}
 	}
 
+	// Whether pos in canvas space is on one of the selected objects
+	fn over_selection(
+		data: &Vector<RenderObject>,
+		pos: Point,
+		pixel_size: f64,
+		tolerance: f64,
+		time: f64,
+	) -> bool {
+		data.iter().any(|object| {
+			object.is_selected()
+				&& object.hit_test(pos, GRAB_RADIUS * pixel_size, tolerance, time)
+		})
+	}
+
 	pub fn get_configuration() -> impl Widget<Self> {
 		Flex::column().with_child(Label::new("Selection Tool"))
 	}
@@ -71,18 +90,22 @@ impl Tool for SelectionTool {
 		event: &druid::Event,
 		_ctx: &mut druid::EventCtx,
 		data: &mut Vector<RenderObject>,
+		snapper: &mut Snapper,
+		env: &druid::Env,
 	) {
 		match event {
 			Event::MouseDown(e) => {
 				self.state = SelectionState::Selecting;
-				self.start_coord = e.pos;
-				self.end_coord = e.pos;
+				self.start_coord = snapper.snap(e.pos, None, false);
+				self.end_coord = self.start_coord;
 			}
 			Event::MouseUp(_) => self.state = SelectionState::Standby,
 			Event::MouseMove(e) => {
 				if let SelectionState::Selecting = self.state {
-					self.end_coord = e.pos;
-					self.update_selected(data);
+					self.end_coord = snapper.snap(e.pos, Some(self.start_coord), e.mods.shift());
+					self.update_selected(data, env.get(COLLISION_TOLERANCE), snapper.time());
+				} else {
+					snapper.snap(e.pos, None, false);
 				}
 			}
 			_ => (),
@@ -97,6 +120,25 @@ impl Tool for SelectionTool {
 			SelectionState::Standby => None,
 		}
 	}
+
+	fn cursor(
+		&self,
+		pos: Point,
+		objects: &Vector<RenderObject>,
+		pixel_size: f64,
+		time: f64,
+		env: &druid::Env,
+	) -> ToolCursor {
+		let tolerance = env.get(COLLISION_TOLERANCE);
+		// Shown over the selection, though not while dragging out a new rectangle
+		if self.state == SelectionState::Standby
+			&& Self::over_selection(objects, pos, pixel_size, tolerance, time)
+		{
+			ToolCursor::Move
+		} else {
+			ToolCursor::Arrow
+		}
+	}
 }
 
 impl Default for SelectionTool {
@@ -104,3 +146,58 @@ impl Default for SelectionTool {
 		Self::new()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::f64::consts::FRAC_PI_4;
+
+	use druid::Affine;
+
+	use super::*;
+	use crate::render_objects::fixtures::square;
+
+	// Whether dragging a selection rectangle between two points selects the object
+	fn marquee_selects(object: RenderObject, from: Point, to: Point) -> bool {
+		let tool = SelectionTool {
+			start_coord: from,
+			end_coord: to,
+			state: SelectionState::Selecting,
+		};
+		let mut data = Vector::unit(object);
+		tool.update_selected(&mut data, 0.5, 0.0);
+		data[0].is_selected()
+	}
+
+	#[test]
+	fn marquee_scaled() {
+		let object = square(Affine::scale(2.0));
+		// Crosses the right side of the scaled square, well clear of the original
+		assert!(marquee_selects(
+			object.clone(),
+			Point::new(15.0, 15.0),
+			Point::new(25.0, 25.0)
+		));
+		assert!(!marquee_selects(
+			object,
+			Point::new(22.0, 0.0),
+			Point::new(30.0, 5.0)
+		));
+	}
+
+	#[test]
+	fn marquee_rotated() {
+		// A diamond standing on the origin with its top at (0, 14.1)
+		let object = square(Affine::rotate(FRAC_PI_4));
+		assert!(marquee_selects(
+			object.clone(),
+			Point::new(-1.0, 12.0),
+			Point::new(1.0, 16.0)
+		));
+		// Crosses the right side of the square before turning, but misses the diamond
+		assert!(!marquee_selects(
+			object,
+			Point::new(5.0, 0.0),
+			Point::new(12.0, 3.0)
+		));
+	}
+}
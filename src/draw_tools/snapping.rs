@@ -0,0 +1,214 @@
+use druid::{
+	im::Vector,
+	kurbo::{Line, PathSeg, Shape},
+	Affine, Data, Lens, Point, Rect, Size, Vec2,
+};
+
+use crate::render_objects::{
+	snap_target::{SnapKind, SnapTarget},
+	ObjectId, RenderObject,
+};
+
+#[derive(Data, Clone, Copy, Debug, PartialEq, Lens)]
+pub struct SnapSettings {
+	pub grid: bool,
+	pub grid_size: f64,
+	pub endpoints: bool,
+	pub midpoints: bool,
+	pub intersections: bool,
+	// Degrees between the angles a line snaps to while shift is held
+	pub angle_step: f64,
+	// Distance in screen pixels within which the pointer snaps to objects
+	pub radius: f64,
+}
+
+impl Default for SnapSettings {
+	fn default() -> Self {
+		Self {
+			grid: false,
+			grid_size: 20.0,
+			endpoints: true,
+			midpoints: true,
+			intersections: true,
+			angle_step: 15.0,
+			radius: 8.0,
+		}
+	}
+}
+
+impl SnapSettings {
+	fn allows(&self, kind: SnapKind) -> bool {
+		match kind {
+			SnapKind::Grid => self.grid,
+			SnapKind::Endpoint => self.endpoints,
+			SnapKind::Midpoint => self.midpoints,
+			SnapKind::Intersection => self.intersections,
+			SnapKind::Angle => true,
+		}
+	}
+}
+
+/// Snaps canvas space positions to the grid and to existing geometry.
+///
+/// A Snapper is built for every event that is passed to a tool, and
+/// remembers the last target it snapped to so that it can be displayed.
+pub struct Snapper {
+	settings: SnapSettings,
+	objects: Vector<RenderObject>,
+	// The size of a screen pixel in canvas units
+	pixel_size: f64,
+	// The animation time the objects are showing
+	time: f64,
+	active: Option<SnapTarget>,
+}
+
+impl Snapper {
+	pub fn new(
+		settings: SnapSettings,
+		objects: Vector<RenderObject>,
+		to_widget: Affine,
+		time: f64,
+	) -> Self {
+		let scale = to_widget.determinant().abs().sqrt();
+		Self {
+			settings,
+			objects,
+			pixel_size: if scale > 0.0 { 1.0 / scale } else { 1.0 },
+			time,
+			active: None,
+		}
+	}
+
+	/// The size of a screen pixel in canvas units
+	pub fn pixel_size(&self) -> f64 {
+		self.pixel_size
+	}
+
+	/// The animation time in seconds the objects are showing, for testing against their shapes
+	pub fn time(&self) -> f64 {
+		self.time
+	}
+
+	/// Stop snapping to an object, such as one that is being reshaped
+	pub fn ignore(&mut self, id: ObjectId) {
+		self.objects.retain(|object| object.id != id);
+	}
+
+	/// The target chosen by the most recent call to snap
+	pub fn active(&self) -> Option<SnapTarget> {
+		self.active
+	}
+
+	/// Snap a position, returning the snapped position.
+	///
+	/// When `constrain_angle` is set and an anchor is given, the position is
+	/// instead constrained to the nearest angle step around the anchor.
+	pub fn snap(&mut self, pos: Point, anchor: Option<Point>, constrain_angle: bool) -> Point {
+		self.active = match anchor {
+			Some(anchor) if constrain_angle => self.angle_target(pos, anchor),
+			_ => self
+				.object_target(pos, self.settings.radius * self.pixel_size)
+				.or_else(|| self.grid_target(pos)),
+		};
+		self.active.map_or(pos, |target| target.point)
+	}
+
+	fn angle_target(&self, pos: Point, anchor: Point) -> Option<SnapTarget> {
+		let delta = pos - anchor;
+		if delta.hypot() == 0.0 || self.settings.angle_step <= 0.0 {
+			return None;
+		}
+		let step = self.settings.angle_step.to_radians();
+		let dir = Vec2::from_angle((delta.atan2() / step).round() * step);
+		Some(SnapTarget::new(
+			anchor + dir * delta.dot(dir),
+			SnapKind::Angle,
+		))
+	}
+
+	fn grid_target(&self, pos: Point) -> Option<SnapTarget> {
+		let size = self.settings.grid_size;
+		if !self.settings.grid || size <= 0.0 {
+			return None;
+		}
+		Some(SnapTarget::new(
+			Point::new((pos.x / size).round() * size, (pos.y / size).round() * size),
+			SnapKind::Grid,
+		))
+	}
+
+	fn object_target(&self, pos: Point, radius: f64) -> Option<SnapTarget> {
+		let mut candidates = Vec::new();
+		for obj in self.objects.iter() {
+			for target in obj.drawable.snap_points() {
+				if self.settings.allows(target.kind) {
+					candidates.push(SnapTarget::new(obj.transform * target.point, target.kind));
+				}
+			}
+		}
+		if self.settings.intersections {
+			candidates.extend(self.intersections_near(pos, radius));
+		}
+		candidates
+			.into_iter()
+			.filter(|target| target.point.distance(pos) <= radius)
+			.min_by(|a, b| {
+				a.point
+					.distance(pos)
+					.partial_cmp(&b.point.distance(pos))
+					.unwrap_or(std::cmp::Ordering::Equal)
+			})
+	}
+
+	// Crossings between the paths of different objects close to pos
+	fn intersections_near(&self, pos: Point, radius: f64) -> Vec<SnapTarget> {
+		let area = Rect::from_center_size(pos, Size::new(radius * 2.0, radius * 2.0));
+		let nearby: Vec<Vec<Line>> = self
+			.objects
+			.iter()
+			.filter(|obj| !obj.bounds().intersect(area).is_empty())
+			.map(|obj| {
+				obj.collision_shape(1.0, self.time)
+					.segments()
+					.filter_map(|seg| match seg {
+						PathSeg::Line(l) => Some(l),
+						_ => None,
+					})
+					.filter(|l| !Rect::from_points(l.p0, l.p1).intersect(area).is_empty())
+					.collect()
+			})
+			.collect();
+
+		let mut found = Vec::new();
+		for (i, a_lines) in nearby.iter().enumerate() {
+			for b_lines in nearby.iter().skip(i + 1) {
+				for a in a_lines {
+					for b in b_lines {
+						if let Some(p) = segment_intersection(*a, *b) {
+							found.push(SnapTarget::new(p, SnapKind::Intersection));
+						}
+					}
+				}
+			}
+		}
+		found
+	}
+}
+
+// The point where two line segments cross, if they do
+fn segment_intersection(a: Line, b: Line) -> Option<Point> {
+	let r = a.p1 - a.p0;
+	let s = b.p1 - b.p0;
+	let denom = r.cross(s);
+	if denom == 0.0 {
+		return None;
+	}
+	let qp = b.p0 - a.p0;
+	let t = qp.cross(s) / denom;
+	let u = qp.cross(r) / denom;
+	if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+		Some(a.p0 + r * t)
+	} else {
+		None
+	}
+}
@@ -1,14 +1,23 @@
+use super::snapping::Snapper;
+use super::EditTool;
 use super::FractalLineTool;
 use super::SelectionTool;
 use crate::render_objects::RenderObject;
-use druid::{im::Vector, Data, Event, EventCtx};
+use druid::{im::Vector, Affine, Data, Event, EventCtx, Point, Rect};
 use druid_enums::Matcher;
 use trait_enum::trait_enum;
 
 pub trait Tool {
 	fn enable(&mut self, data: &mut Vector<RenderObject>);
 	fn disable(&mut self, data: &mut Vector<RenderObject>);
-	fn event(&mut self, event: &Event, ctx: &mut EventCtx, data: &mut Vector<RenderObject>);
+	fn event(
+		&mut self,
+		event: &Event,
+		ctx: &mut EventCtx,
+		data: &mut Vector<RenderObject>,
+		snapper: &mut Snapper,
+		env: &druid::Env,
+	);
 
 	fn get_preview(&self) -> Option<RenderObject>;
 
@@ -17,6 +26,44 @@ pub trait Tool {
 			robj.paint(ctx, env)
 		}
 	}
+
+	// Paint anything that should stay the same size on screen, such as handles, in widget space
+	fn paint_overlay(
+		&self,
+		_ctx: &mut druid::PaintCtx,
+		_to_widget: Affine,
+		_objects: &Vector<RenderObject>,
+	) {
+	}
+
+	// The widget space areas painted by paint_overlay, so that they can be invalidated
+	fn overlay_rects(&self, _to_widget: Affine, _objects: &Vector<RenderObject>) -> Vec<Rect> {
+		Vec::new()
+	}
+
+	// The cursor to show with the pointer at pos in canvas space, where pixel_size is the size of a
+	// screen pixel in canvas units and time is the animation time the objects are showing
+	fn cursor(
+		&self,
+		_pos: Point,
+		_objects: &Vector<RenderObject>,
+		_pixel_size: f64,
+		_time: f64,
+		_env: &druid::Env,
+	) -> ToolCursor {
+		ToolCursor::Arrow
+	}
+}
+
+/// The pointer a tool asks for over the canvas, which the canvas turns into a platform cursor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToolCursor {
+	Arrow,
+	Crosshair,
+	/// Arrows pointing four ways, over something that dragging would move
+	Move,
+	ResizeLeftRight,
+	ResizeUpDown,
 }
 
 trait_enum! {
@@ -24,5 +71,6 @@ trait_enum! {
 	pub enum ToolObj : Tool {
 		FractalLineTool,
 		SelectionTool,
+		EditTool,
 	}
 }
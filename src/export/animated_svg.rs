@@ -0,0 +1,177 @@
+use druid::{
+	im::Vector,
+	kurbo::{BezPath, Shape},
+	FileInfo, Rect, Selector,
+};
+
+use crate::path_ops::Simplification;
+use crate::render_objects::{
+	stroke::StrokeMode, Drawable, FractalLine, MultiStrandLine, RenderObject,
+};
+
+/// Sent by the save dialog once the user has chosen where to export an animation
+pub const EXPORT_ANIMATION: Selector<FileInfo> = Selector::new("lipuma.export-animation");
+
+/// Length in seconds of an exported animation loop
+pub const EXPORT_DURATION: f64 = 4.0;
+pub const EXPORT_FPS: f64 = 24.0;
+
+// A single svg path element, with one path per frame if it is animated
+struct AnimatedPath {
+	frames: Vec<BezPath>,
+	filled: bool,
+	width: f64,
+	opacity: f64,
+}
+
+impl AnimatedPath {
+	fn new(
+		times: &[f64],
+		opacity: f64,
+		simplification: Simplification,
+		line_at: impl Fn(f64) -> FractalLine,
+	) -> Self {
+		let first = line_at(0.0);
+		let animated = first.animation.is_animated();
+		let times = if animated { times } else { &times[..1] };
+		let frames: Vec<BezPath> = times
+			.iter()
+			.map(|t| {
+				let line = line_at(*t);
+				match line.stroke.mode {
+					StrokeMode::Line => line.to_path(0.1),
+					StrokeMode::Filled => line.outline(0.1),
+				}
+			})
+			.collect();
+		Self {
+			frames: simplification.apply_to_frames(&frames),
+			filled: first.stroke.mode == StrokeMode::Filled,
+			width: first.stroke.width,
+			opacity,
+		}
+	}
+
+	fn to_svg(&self) -> String {
+		let paint = if self.filled {
+			r#"fill="black" stroke="none""#.to_string()
+		} else {
+			format!(
+				r#"fill="none" stroke="black" stroke-width="{}""#,
+				self.width
+			)
+		};
+		let d = self.frames[0].to_svg();
+		if self.frames.len() == 1 {
+			return format!(r#"<path d="{}" {} opacity="{}"/>"#, d, paint, self.opacity);
+		}
+		// Ending on the first frame again closes the loop, so that it repeats without a jump
+		let values = self
+			.frames
+			.iter()
+			.chain(self.frames.first())
+			.map(|f| f.to_svg())
+			.collect::<Vec<_>>()
+			.join(";");
+		format!(
+			r#"<path d="{}" {} opacity="{}"><animate attributeName="d" dur="{}s" repeatCount="indefinite" values="{}"/></path>"#,
+			d, paint, self.opacity, EXPORT_DURATION, values
+		)
+	}
+}
+
+fn object_paths(
+	object: &RenderObject,
+	times: &[f64],
+	simplification: Simplification,
+) -> Vec<AnimatedPath> {
+	match &object.drawable {
+		Drawable::FractalLine(line) => {
+			vec![AnimatedPath::new(times, 1.0, simplification, |t| {
+				line.at_time(t)
+			})]
+		}
+		Drawable::MultiStrandLine(multi) => (0..multi.strands.count.max(1))
+			.map(|i| {
+				AnimatedPath::new(times, multi.strand_opacity(i), simplification, |t| {
+					MultiStrandLine::new(multi.line.at_time(t), multi.strands).strand(i)
+				})
+			})
+			.collect(),
+		// Converted paths are already fixed, so are never animated
+		Drawable::PathShape(shape) => vec![AnimatedPath {
+			frames: vec![simplification.apply(&shape.path)],
+			filled: shape.filled,
+			width: shape.width,
+			opacity: shape.opacity,
+		}],
+		// Selection rectangles are never part of the artwork
+		Drawable::SelectionRect(_) => Vec::new(),
+	}
+}
+
+/// Build an svg document that loops the animation of every object
+pub fn animated_svg(objects: &Vector<RenderObject>, simplification: Simplification) -> String {
+	let times: Vec<f64> = (0..(EXPORT_DURATION * EXPORT_FPS) as usize)
+		.map(|frame| frame as f64 / EXPORT_FPS)
+		.collect();
+	let bounds = objects
+		.iter()
+		.map(|obj| obj.exact_bounds())
+		.reduce(|acc, rect| acc.union(rect))
+		.unwrap_or(Rect::ZERO);
+
+	let mut svg = format!(
+		r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+		bounds.x0,
+		bounds.y0,
+		bounds.width(),
+		bounds.height()
+	);
+	for object in objects.iter() {
+		let c = object.transform.as_coeffs();
+		svg.push_str(&format!(
+			r#"<g transform="matrix({} {} {} {} {} {})">"#,
+			c[0], c[1], c[2], c[3], c[4], c[5]
+		));
+		for path in object_paths(object, &times, simplification) {
+			svg.push_str(&path.to_svg());
+		}
+		svg.push_str("</g>");
+	}
+	svg.push_str("</svg>");
+	svg
+}
+
+#[cfg(test)]
+mod tests {
+	use std::f64::consts::FRAC_PI_4;
+
+	use druid::Affine;
+
+	use super::*;
+	use crate::render_objects::fixtures::square;
+
+	#[test]
+	fn export_goes_through_object_transforms() {
+		let transform = Affine::translate((30.0, 0.0)) * Affine::rotate(FRAC_PI_4);
+		let object = square(transform);
+		let bounds = object.exact_bounds();
+		let svg = animated_svg(&Vector::unit(object), Simplification::default());
+
+		let c = transform.as_coeffs();
+		assert!(svg.contains(&format!(
+			r#"<g transform="matrix({} {} {} {} {} {})">"#,
+			c[0], c[1], c[2], c[3], c[4], c[5]
+		)));
+		// The view covers the turned and moved square, not where it is before transforming
+		assert!(svg.contains(&format!(
+			r#"viewBox="{} {} {} {}""#,
+			bounds.x0,
+			bounds.y0,
+			bounds.width(),
+			bounds.height()
+		)));
+		assert!(bounds.x0 > 20.0 && bounds.x1 < 40.0 && bounds.y1 > 14.0);
+	}
+}
@@ -0,0 +1,3 @@
+pub mod animated_svg;
+
+pub use animated_svg::{animated_svg, EXPORT_ANIMATION};
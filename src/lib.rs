@@ -1,3 +1,9 @@
+pub mod app_commands;
+pub mod config;
 pub mod draw_tools;
+pub mod export;
+pub mod noise;
+pub mod path_ops;
+pub mod preferences;
 pub mod render_objects;
 pub mod widgets;
@@ -1,29 +1,48 @@
-use druid::widget::Flex;
+use std::sync::Arc;
+
+use druid::widget::{EnvScope, Flex};
 use druid::{AppLauncher, PlatformError, Widget, WindowDesc};
-use rust_lipuma::draw_tools::{FractalLineTool, SelectionTool, Tool};
+use rust_lipuma::app_commands::Keymap;
+use rust_lipuma::draw_tools::{presets::PresetLibrary, FractalLineTool, Tool};
+use rust_lipuma::preferences::Preferences;
 
 use rust_lipuma::widgets::compose_widgets::*;
-use rust_lipuma::widgets::{graphics_data::GraphicsData, graphics_scene_widget::*};
+use rust_lipuma::widgets::{
+	graphics_data::GraphicsData, graphics_scene_widget::*, tooltip::TooltipLayer,
+};
 
 fn build_ui() -> impl Widget<GraphicsData> {
 	let mut row = Flex::row();
 	row.add_child(
 		Flex::column()
-			.with_child(tool_selection_button(
-				Tool::FractalLineTool(FractalLineTool::default()),
-				"Fractal Line Tool",
-			))
-			.with_child(tool_selection_button(
-				Tool::SelectionTool(SelectionTool::default()),
-				"Selection Tool",
-			)),
+			.with_child(toolbar())
+			.with_child(animation_controls())
+			.with_child(snap_settings())
+			.with_child(overlay_settings())
+			.with_child(view_controls())
+			.with_child(path_controls()),
 	);
 	row.add_flex_child(GraphicsWidget::new(), 1.0);
 	row.add_child(settings_menu());
-	row
+	// Republish the preferences whenever they change so they apply immediately
+	let ui = EnvScope::new(|env, data: &GraphicsData| data.preferences.apply(env), row);
+	TooltipLayer::new(ui)
 }
 
 fn main() -> Result<(), PlatformError> {
-	AppLauncher::with_window(WindowDesc::new(build_ui)).launch(GraphicsData::new())?;
+	let presets = PresetLibrary::load();
+	let mut tool = FractalLineTool::new();
+	if let Some(preset) = presets.active_preset() {
+		tool.apply_preset(preset);
+	}
+	AppLauncher::with_window(WindowDesc::new(build_ui))
+		.configure_env(|env, data: &GraphicsData| data.preferences.apply(env))
+		.launch(GraphicsData {
+			keymap: Arc::new(Keymap::load()),
+			tool: Tool::FractalLineTool(tool),
+			presets,
+			preferences: Preferences::load(),
+			..GraphicsData::new()
+		})?;
 	Ok(())
 }
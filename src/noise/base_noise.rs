@@ -0,0 +1,109 @@
+use druid::Data;
+use trait_enum::trait_enum;
+
+use super::{Interpolation, PerlinNoise, SimplexNoise, ValueNoise, WorleyNoise};
+
+pub trait Noise {
+	/// Sample a single octave of noise at `x`.
+	///
+	/// Results are expected to lie roughly within [-1, 1] so that every
+	/// implementation can be swapped in without rescaling the line.
+	fn sample(&self, seed: u32, x: f64) -> f64;
+}
+
+trait_enum! {
+	#[derive(Data, Clone, Copy, Debug, PartialEq)]
+	pub enum NoiseObj: Noise {
+		ValueNoise,
+		PerlinNoise,
+		SimplexNoise,
+		WorleyNoise,
+	}
+}
+
+impl NoiseObj {
+	/// Every selectable noise algorithm along with a display name
+	pub fn variants() -> Vec<(&'static str, NoiseObj)> {
+		vec![
+			(
+				"Value (smoothstep)",
+				NoiseObj::ValueNoise(ValueNoise::new(Interpolation::Smoothstep)),
+			),
+			(
+				"Value (cosine)",
+				NoiseObj::ValueNoise(ValueNoise::new(Interpolation::Cosine)),
+			),
+			(
+				"Value (quintic)",
+				NoiseObj::ValueNoise(ValueNoise::new(Interpolation::Quintic)),
+			),
+			("Perlin", NoiseObj::PerlinNoise(PerlinNoise)),
+			("Simplex", NoiseObj::SimplexNoise(SimplexNoise)),
+			("Worley", NoiseObj::WorleyNoise(WorleyNoise)),
+		]
+	}
+}
+
+impl Default for NoiseObj {
+	fn default() -> Self {
+		NoiseObj::ValueNoise(ValueNoise::default())
+	}
+}
+
+/// How each octave is shaped before being summed into the fractal
+#[derive(Data, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OctaveStyle {
+	Standard,
+	// Folds the noise around zero, giving puffy rounded bumps
+	Billow,
+	// Inverted billow, giving sharp crests
+	Ridged,
+}
+
+impl OctaveStyle {
+	pub fn variants() -> Vec<(&'static str, OctaveStyle)> {
+		vec![
+			("Standard", OctaveStyle::Standard),
+			("Billow", OctaveStyle::Billow),
+			("Ridged", OctaveStyle::Ridged),
+		]
+	}
+
+	#[inline(always)]
+	pub fn apply(&self, n: f64) -> f64 {
+		match self {
+			OctaveStyle::Standard => n,
+			OctaveStyle::Billow => 2.0 * n.abs() - 1.0,
+			OctaveStyle::Ridged => 1.0 - 2.0 * n.abs(),
+		}
+	}
+}
+
+impl Default for OctaveStyle {
+	fn default() -> Self {
+		OctaveStyle::Standard
+	}
+}
+
+// Information taken from skeeto/hash-prospector
+#[inline(always)]
+pub(crate) fn get_hash(mut i: u32) -> u32 {
+	i ^= i >> 16;
+	i = i.wrapping_mul(0x21f0aaad);
+	i ^= i >> 15;
+	i = i.wrapping_mul(0xd35a2d97);
+	i ^= i >> 15;
+	i
+}
+
+// Hash a lattice cell, mixing in the seed so neighbouring seeds are uncorrelated
+#[inline(always)]
+pub(crate) fn hash_cell(seed: u32, cell: f64) -> u32 {
+	get_hash(seed ^ get_hash(cell as i64 as u32))
+}
+
+// Map a hash to the range [0, 1]
+#[inline(always)]
+pub(crate) fn unit(hash: u32) -> f64 {
+	hash as f64 / u32::MAX as f64
+}
@@ -0,0 +1,12 @@
+pub mod base_noise;
+pub mod perlin_noise;
+pub mod simplex_noise;
+pub mod value_noise;
+pub mod worley_noise;
+
+pub use base_noise::NoiseObj;
+pub use base_noise::OctaveStyle;
+pub use perlin_noise::PerlinNoise;
+pub use simplex_noise::SimplexNoise;
+pub use value_noise::{Interpolation, ValueNoise};
+pub use worley_noise::WorleyNoise;
@@ -0,0 +1,30 @@
+use druid::Data;
+
+use super::base_noise::{hash_cell, unit, Noise};
+
+/// Classic gradient noise, a random slope at each integer
+#[derive(Data, Clone, Copy, Debug, Default, PartialEq)]
+pub struct PerlinNoise;
+
+impl PerlinNoise {
+	#[inline(always)]
+	fn gradient(seed: u32, cell: f64) -> f64 {
+		unit(hash_cell(seed, cell)) * 2.0 - 1.0
+	}
+
+	#[inline(always)]
+	fn fade(t: f64) -> f64 {
+		t.powi(3) * (t * (t * 6.0 - 15.0) + 10.0)
+	}
+}
+
+impl Noise for PerlinNoise {
+	fn sample(&self, seed: u32, x: f64) -> f64 {
+		let floor = x.floor();
+		let fract = x - floor;
+		let start = Self::gradient(seed, floor) * fract;
+		let end = Self::gradient(seed, floor + 1.0) * (fract - 1.0);
+		// 1D gradient noise peaks at 0.5, so rescale to fill [-1, 1]
+		(start + Self::fade(fract) * (end - start)) * 2.0
+	}
+}
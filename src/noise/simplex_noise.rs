@@ -0,0 +1,36 @@
+use druid::Data;
+
+use super::base_noise::{hash_cell, Noise};
+
+/// Simplex noise, following Stefan Gustavson's reference implementation
+#[derive(Data, Clone, Copy, Debug, Default, PartialEq)]
+pub struct SimplexNoise;
+
+impl SimplexNoise {
+	#[inline(always)]
+	fn gradient(hash: u32, x: f64) -> f64 {
+		let h = hash & 15;
+		let grad = 1.0 + (h & 7) as f64;
+		if h & 8 != 0 {
+			-grad * x
+		} else {
+			grad * x
+		}
+	}
+
+	#[inline(always)]
+	fn contribution(seed: u32, cell: f64, x: f64) -> f64 {
+		let t = 1.0 - x * x;
+		t.powi(4) * Self::gradient(hash_cell(seed, cell), x)
+	}
+}
+
+impl Noise for SimplexNoise {
+	fn sample(&self, seed: u32, x: f64) -> f64 {
+		let floor = x.floor();
+		let x0 = x - floor;
+		let x1 = x0 - 1.0;
+		// Scale factor from the reference implementation, bringing output into [-1, 1]
+		0.395 * (Self::contribution(seed, floor, x0) + Self::contribution(seed, floor + 1.0, x1))
+	}
+}
@@ -0,0 +1,55 @@
+use std::f64::consts::PI;
+
+use druid::Data;
+
+use super::base_noise::{get_hash, Noise};
+
+#[derive(Data, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+	Smoothstep,
+	Cosine,
+	Quintic,
+}
+
+impl Interpolation {
+	#[inline(always)]
+	fn interpolate(&self, start: f64, end: f64, x: f64) -> f64 {
+		let t = match self {
+			Interpolation::Smoothstep => (3.0 * x.powi(2)) - (2.0 * x.powi(3)),
+			Interpolation::Cosine => (1.0 - (x * PI).cos()) / 2.0,
+			Interpolation::Quintic => x.powi(3) * (x * (x * 6.0 - 15.0) + 10.0),
+		};
+		start + t * (end - start)
+	}
+}
+
+/// Random values at each integer, interpolated between
+#[derive(Data, Clone, Copy, Debug, PartialEq)]
+pub struct ValueNoise {
+	pub interpolation: Interpolation,
+}
+
+impl ValueNoise {
+	pub fn new(interpolation: Interpolation) -> Self {
+		Self { interpolation }
+	}
+}
+
+impl Default for ValueNoise {
+	fn default() -> Self {
+		Self::new(Interpolation::Smoothstep)
+	}
+}
+
+impl Noise for ValueNoise {
+	fn sample(&self, seed: u32, x: f64) -> f64 {
+		let floor = x.floor();
+		let (ceil, fract) = ((floor + 1.0) as u32, x - floor);
+		let floor = floor as u32;
+		self.interpolation.interpolate(
+			(get_hash(seed.wrapping_mul(floor)) % 3) as f64 - 1.0,
+			(get_hash(seed.wrapping_mul(ceil)) % 3) as f64 - 1.0,
+			fract,
+		)
+	}
+}
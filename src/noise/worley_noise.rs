@@ -0,0 +1,35 @@
+use druid::Data;
+
+use super::base_noise::{hash_cell, unit, Noise};
+
+/// Cellular noise, the distance to the nearest randomly placed feature point
+#[derive(Data, Clone, Copy, Debug, Default, PartialEq)]
+pub struct WorleyNoise;
+
+impl Noise for WorleyNoise {
+	fn sample(&self, seed: u32, x: f64) -> f64 {
+		let floor = x.floor();
+		// Feature points of the neighbouring cells, in order as each lies within its own cell
+		let features =
+			[floor - 1.0, floor, floor + 1.0].map(|cell| cell + unit(hash_cell(seed, cell)));
+		// In one dimension x always lies between two of them
+		let before = features
+			.iter()
+			.copied()
+			.filter(|f| *f <= x)
+			.fold(f64::MIN, f64::max);
+		let after = features
+			.iter()
+			.copied()
+			.filter(|f| *f > x)
+			.fold(f64::MAX, f64::min);
+		let nearest = (x - before).min(after - x);
+		// Relative to half the gap the distance is spread evenly over [0, 1], so it maps onto
+		// [-1, 1] centred on zero like the other algorithms
+		let half_gap = (after - before) / 2.0;
+		if half_gap <= 0.0 {
+			return 0.0;
+		}
+		(nearest / half_gap).min(1.0) * 2.0 - 1.0
+	}
+}
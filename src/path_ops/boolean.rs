@@ -0,0 +1,412 @@
+use std::collections::{HashMap, HashSet};
+
+use druid::kurbo::{BezPath, Line, PathEl, Point, Rect, Vec2};
+
+// Lengths below this are treated as zero when splitting and joining edges
+const EPSILON: f64 = 1e-9;
+// Points are matched up when joining edges after rounding to this many units
+const JOIN_PRECISION: f64 = 1e-6;
+// Distance to either side of a piece of edge at which the result is tested for, small enough
+// not to reach past any neighbouring edge
+const SIDE_OFFSET: f64 = 1e-4;
+
+/// How two filled areas are combined
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BooleanOp {
+	/// Everything covered by either area
+	Union,
+	/// Only what is covered by both areas
+	Intersection,
+	/// The first area with the second cut out of it
+	Difference,
+}
+
+/// Combine two filled areas made of straight lines, giving the outline of the result.
+///
+/// Both areas use the nonzero fill rule. Every edge is split wherever another edge of either
+/// area crosses or runs along it, including edges of the same area. A piece is kept if the
+/// result lies on one side of it but not the other, and the kept pieces are joined back up
+/// into closed loops with the result on their left.
+pub fn combine(a: &BezPath, b: &BezPath, op: BooleanOp) -> BezPath {
+	let a_rings = rings(a);
+	let b_rings = rings(b);
+	let in_result = |p: Point| op.keeps(winding(&a_rings, p) != 0, winding(&b_rings, p) != 0);
+
+	let mut all_edges = edges(&a_rings);
+	all_edges.extend(edges(&b_rings));
+	let splits = splits(&all_edges);
+
+	let mut seen = HashSet::new();
+	let kept: Vec<Line> = pieces(&all_edges, splits)
+		.filter_map(|piece| {
+			let direction = piece.p1 - piece.p0;
+			let left = Vec2::new(direction.y, -direction.x).normalize() * SIDE_OFFSET;
+			let middle = piece.p0.midpoint(piece.p1);
+			match (in_result(middle + left), in_result(middle - left)) {
+				(true, false) => Some(piece),
+				(false, true) => Some(Line::new(piece.p1, piece.p0)),
+				_ => None,
+			}
+		})
+		// Edges the areas share give the same piece twice, once from each
+		.filter(|piece| seen.insert((join_key(piece.p0), join_key(piece.p1))))
+		.collect();
+	join(kept)
+}
+
+impl BooleanOp {
+	// Whether a point covered or not by each area is covered by the result
+	fn keeps(self, in_a: bool, in_b: bool) -> bool {
+		match self {
+			BooleanOp::Union => in_a || in_b,
+			BooleanOp::Intersection => in_a && in_b,
+			BooleanOp::Difference => in_a && !in_b,
+		}
+	}
+}
+
+// Each closed subpath of a path as a list of points
+fn rings(path: &BezPath) -> Vec<Vec<Point>> {
+	let mut rings = Vec::new();
+	let mut ring: Vec<Point> = Vec::new();
+	for el in path.elements() {
+		match *el {
+			PathEl::MoveTo(p) => {
+				rings.push(std::mem::take(&mut ring));
+				ring.push(p);
+			}
+			PathEl::LineTo(p) | PathEl::QuadTo(_, p) | PathEl::CurveTo(_, _, p) => {
+				if ring.last().map_or(true, |last| last.distance(p) > EPSILON) {
+					ring.push(p);
+				}
+			}
+			PathEl::ClosePath => rings.push(std::mem::take(&mut ring)),
+		}
+	}
+	rings.push(ring);
+	for ring in rings.iter_mut() {
+		if ring.len() > 1 && ring[0].distance(ring[ring.len() - 1]) <= EPSILON {
+			ring.pop();
+		}
+	}
+	rings.retain(|ring| ring.len() >= 3);
+	rings
+}
+
+// Winding number of the rings around a point, counted here rather than with Shape::winding as
+// the result for slanted lines differs between kurbo versions
+fn winding(rings: &[Vec<Point>], p: Point) -> i32 {
+	rings
+		.iter()
+		.flat_map(|ring| ring.iter().zip(ring.iter().cycle().skip(1)))
+		.map(|(p0, p1)| {
+			let side = (*p1 - *p0).cross(p - *p0);
+			if p0.y <= p.y && p.y < p1.y && side > 0.0 {
+				1
+			} else if p1.y <= p.y && p.y < p0.y && side < 0.0 {
+				-1
+			} else {
+				0
+			}
+		})
+		.sum()
+}
+
+fn edges(rings: &[Vec<Point>]) -> Vec<Line> {
+	rings
+		.iter()
+		.flat_map(|ring| {
+			ring.iter()
+				.zip(ring.iter().cycle().skip(1))
+				.map(|(p0, p1)| Line::new(*p0, *p1))
+		})
+		.collect()
+}
+
+// Where each edge is crossed or touched by any other edge, as (position along the edge, point).
+// Both edges get exactly the same point so that the pieces join up afterwards.
+fn splits(edges: &[Line]) -> Vec<Vec<(f64, Point)>> {
+	let mut splits = vec![Vec::new(); edges.len()];
+	let bounds: Vec<Rect> = edges
+		.iter()
+		.map(|l| Rect::from_points(l.p0, l.p1))
+		.collect();
+	for i in 0..edges.len() {
+		for j in i + 1..edges.len() {
+			if !overlaps(bounds[i], bounds[j]) {
+				continue;
+			}
+			if let Some((t, u)) = crossing(edges[i], edges[j]) {
+				let point = edges[i].p0.lerp(edges[i].p1, t);
+				splits[i].push((t, point));
+				splits[j].push((u, point));
+				continue;
+			}
+			// Edges running along each other are cut at each other's ends, so that the stretch
+			// they share becomes the same piece of both
+			for (edge, other) in [(i, j), (j, i)] {
+				for end in [edges[other].p0, edges[other].p1] {
+					if let Some(t) = position_on(edges[edge], end) {
+						splits[edge].push((t, end));
+					}
+				}
+			}
+		}
+	}
+	splits
+}
+
+// Unlike Rect::intersect this counts rectangles with no width or height, as horizontal and
+// vertical edges have
+fn overlaps(a: Rect, b: Rect) -> bool {
+	a.x0 <= b.x1 && b.x0 <= a.x1 && a.y0 <= b.y1 && b.y0 <= a.y1
+}
+
+// Where a point lies along a line as a position from 0 to 1, if it is on the line
+fn position_on(line: Line, p: Point) -> Option<f64> {
+	let direction = line.p1 - line.p0;
+	let length_sq = direction.hypot2();
+	if length_sq <= EPSILON * EPSILON {
+		return None;
+	}
+	let t = (p - line.p0).dot(direction) / length_sq;
+	let off_line = (line.p0 + direction * t).distance(p);
+	if (0.0..=1.0).contains(&t) && off_line <= JOIN_PRECISION {
+		Some(t)
+	} else {
+		None
+	}
+}
+
+// Positions along two lines where they cross, if they do
+fn crossing(a: Line, b: Line) -> Option<(f64, f64)> {
+	let r = a.p1 - a.p0;
+	let s = b.p1 - b.p0;
+	let denominator = r.cross(s);
+	// Parallel lines never cross, overlapping ones are cut by position_on instead
+	if denominator.abs() < EPSILON {
+		return None;
+	}
+	let between = b.p0 - a.p0;
+	let t = between.cross(s) / denominator;
+	let u = between.cross(r) / denominator;
+	if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+		Some((t, u))
+	} else {
+		None
+	}
+}
+
+// The edges cut at their crossing points, dropping any pieces too short to matter
+fn pieces(edges: &[Line], splits: Vec<Vec<(f64, Point)>>) -> impl Iterator<Item = Line> + '_ {
+	edges
+		.iter()
+		.zip(splits)
+		.flat_map(|(edge, mut cuts)| {
+			cuts.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap_or(std::cmp::Ordering::Equal));
+			let points: Vec<Point> = std::iter::once(edge.p0)
+				.chain(cuts.into_iter().map(|(_, p)| p))
+				.chain(std::iter::once(edge.p1))
+				.collect();
+			points
+				.windows(2)
+				.map(|w| Line::new(w[0], w[1]))
+				.collect::<Vec<_>>()
+		})
+		.filter(|piece| piece.p0.distance(piece.p1) > EPSILON)
+}
+
+fn join_key(p: Point) -> (i64, i64) {
+	(
+		(p.x / JOIN_PRECISION).round() as i64,
+		(p.y / JOIN_PRECISION).round() as i64,
+	)
+}
+
+// Chain directed pieces end to start into closed loops
+fn join(pieces: Vec<Line>) -> BezPath {
+	let mut starting_at: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+	for (i, piece) in pieces.iter().enumerate() {
+		starting_at.entry(join_key(piece.p0)).or_default().push(i);
+	}
+	let mut used = vec![false; pieces.len()];
+	let mut path = BezPath::new();
+	for first in 0..pieces.len() {
+		if used[first] {
+			continue;
+		}
+		used[first] = true;
+		let start = join_key(pieces[first].p0);
+		let mut ring = vec![pieces[first].p0];
+		let mut current = first;
+		loop {
+			let end = pieces[current].p1;
+			if join_key(end) == start {
+				break;
+			}
+			ring.push(end);
+			let next = starting_at
+				.get(&join_key(end))
+				.and_then(|candidates| candidates.iter().copied().find(|&i| !used[i]));
+			match next {
+				Some(next) => {
+					used[next] = true;
+					current = next;
+				}
+				// Only happens with degenerate input, such as edges shorter than the join precision
+				None => break,
+			}
+		}
+		if ring.len() >= 3 {
+			path.move_to(ring[0]);
+			for p in &ring[1..] {
+				path.line_to(*p);
+			}
+			path.close_path();
+		}
+	}
+	path
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use druid::kurbo::Shape;
+
+	fn square(x0: f64, y0: f64, size: f64) -> BezPath {
+		Rect::new(x0, y0, x0 + size, y0 + size).to_path(0.1)
+	}
+
+	fn polygon(points: &[(f64, f64)]) -> BezPath {
+		let mut path = BezPath::new();
+		path.move_to(points[0]);
+		for p in &points[1..] {
+			path.line_to(*p);
+		}
+		path.close_path();
+		path
+	}
+
+	fn loops(path: &BezPath) -> usize {
+		path.elements()
+			.iter()
+			.filter(|el| matches!(el, PathEl::ClosePath))
+			.count()
+	}
+
+	fn covers(path: &BezPath, x: f64, y: f64) -> bool {
+		winding(&rings(path), Point::new(x, y)) != 0
+	}
+
+	fn assert_area(path: &BezPath, expected: f64) {
+		// Every loop has the result on the same side, so no part of the area cancels another
+		assert!(
+			(path.area().abs() - expected).abs() < 1e-6,
+			"area {} is not {}",
+			path.area(),
+			expected
+		);
+	}
+
+	#[test]
+	fn union_of_overlapping_squares() {
+		let result = combine(
+			&square(0.0, 0.0, 10.0),
+			&square(5.0, 5.0, 10.0),
+			BooleanOp::Union,
+		);
+		assert_area(&result, 175.0);
+		assert_eq!(loops(&result), 1);
+		assert!(
+			covers(&result, 2.0, 2.0) && covers(&result, 7.0, 7.0) && covers(&result, 12.0, 12.0)
+		);
+		assert!(!covers(&result, 12.0, 2.0));
+	}
+
+	#[test]
+	fn intersection_of_overlapping_squares() {
+		let result = combine(
+			&square(0.0, 0.0, 10.0),
+			&square(5.0, 5.0, 10.0),
+			BooleanOp::Intersection,
+		);
+		assert_area(&result, 25.0);
+		assert!(covers(&result, 7.0, 7.0));
+		assert!(!covers(&result, 2.0, 2.0) && !covers(&result, 12.0, 12.0));
+	}
+
+	#[test]
+	fn difference_of_overlapping_squares() {
+		let result = combine(
+			&square(0.0, 0.0, 10.0),
+			&square(5.0, 5.0, 10.0),
+			BooleanOp::Difference,
+		);
+		assert_area(&result, 75.0);
+		assert!(covers(&result, 2.0, 2.0));
+		assert!(!covers(&result, 7.0, 7.0) && !covers(&result, 12.0, 12.0));
+	}
+
+	#[test]
+	fn difference_can_cut_a_hole() {
+		let result = combine(
+			&square(0.0, 0.0, 10.0),
+			&square(3.0, 3.0, 4.0),
+			BooleanOp::Difference,
+		);
+		assert_area(&result, 84.0);
+		assert_eq!(loops(&result), 2);
+		assert!(covers(&result, 1.0, 1.0) && !covers(&result, 5.0, 5.0));
+	}
+
+	#[test]
+	fn intersection_of_separate_squares_is_empty() {
+		let result = combine(
+			&square(0.0, 0.0, 10.0),
+			&square(20.0, 0.0, 10.0),
+			BooleanOp::Intersection,
+		);
+		assert!(result.elements().is_empty());
+	}
+
+	#[test]
+	fn union_removes_shared_edges() {
+		// Side by side, so the right edge of one runs along the left edge of the other
+		let result = combine(
+			&square(0.0, 0.0, 10.0),
+			&square(10.0, 0.0, 10.0),
+			BooleanOp::Union,
+		);
+		assert_area(&result, 200.0);
+		assert_eq!(loops(&result), 1);
+	}
+
+	#[test]
+	fn union_with_partly_shared_edge() {
+		let result = combine(
+			&square(0.0, 0.0, 10.0),
+			&square(10.0, 5.0, 10.0),
+			BooleanOp::Union,
+		);
+		assert_area(&result, 200.0);
+		assert_eq!(loops(&result), 1);
+	}
+
+	#[test]
+	fn identical_squares() {
+		let a = square(0.0, 0.0, 10.0);
+		assert_area(&combine(&a, &a, BooleanOp::Union), 100.0);
+		assert_area(&combine(&a, &a, BooleanOp::Intersection), 100.0);
+		assert!(combine(&a, &a, BooleanOp::Difference).elements().is_empty());
+	}
+
+	#[test]
+	fn self_intersecting_operand_is_split() {
+		// A bow tie, whose two triangles wind in opposite directions
+		let bow_tie = polygon(&[(0.0, 0.0), (10.0, 10.0), (10.0, 0.0), (0.0, 10.0)]);
+		let result = combine(&bow_tie, &square(20.0, 20.0, 1.0), BooleanOp::Union);
+		assert_area(&result, 51.0);
+		assert!(covers(&result, 1.0, 4.0) && covers(&result, 9.0, 6.0));
+		assert!(!covers(&result, 4.0, 1.0) && !covers(&result, 6.0, 9.0));
+	}
+}
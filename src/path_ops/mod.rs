@@ -0,0 +1,314 @@
+//! Operations that turn objects into plain paths and combine them.
+//!
+//! Everything here works on the selected objects of a scene, leaving the rest alone.
+pub mod boolean;
+pub mod simplify;
+
+use std::sync::Arc;
+
+use druid::{
+	im::Vector,
+	kurbo::{BezPath, Point, Vec2},
+};
+
+use crate::render_objects::{Drawable, PathShape, RenderObject};
+pub use boolean::BooleanOp;
+pub use simplify::Simplification;
+
+// Tolerance used to flatten curves before combining shapes
+const FLATTEN_TOLERANCE: f64 = 0.1;
+// Sharp corners of a widened stroke reach at most this many half widths out, like the miter
+// limit of a painted one
+const MITER_LIMIT: f64 = 10.0;
+
+/// The fixed paths an object is drawn with at an animation time, or None if it already is one
+pub fn to_paths(
+	object: &RenderObject,
+	time: f64,
+	simplification: Simplification,
+) -> Option<Vec<RenderObject>> {
+	let shapes = match &object.drawable {
+		Drawable::FractalLine(line) => vec![line.at_time(time).to_path_shape()],
+		// Each strand keeps its own opacity, so it becomes a separate path
+		Drawable::MultiStrandLine(multi) => (0..multi.strands.count.max(1))
+			.map(|i| {
+				let mut moment = *multi;
+				moment.line = multi.line.at_time(time);
+				moment
+					.strand(i)
+					.to_path_shape()
+					.with_opacity(multi.strand_opacity(i))
+			})
+			.collect(),
+		_ => return None,
+	};
+	Some(
+		shapes
+			.into_iter()
+			.map(|shape| RenderObject {
+				transform: object.transform,
+				selected: object.selected,
+				..RenderObject::new(Drawable::PathShape(PathShape {
+					path: Arc::new(simplification.apply(&shape.path)),
+					..shape
+				}))
+			})
+			.collect(),
+	)
+}
+
+/// Replace every selected object that can be converted with its paths, in the same place in
+/// the drawing order
+pub fn convert_selected(
+	objects: &mut Vector<RenderObject>,
+	time: f64,
+	simplification: Simplification,
+) {
+	let mut converted = Vector::new();
+	for object in objects.iter() {
+		let paths = to_paths(object, time, simplification).filter(|_| object.is_selected());
+		match paths {
+			Some(paths) => converted.extend(paths),
+			None => converted.push_back(object.clone()),
+		}
+	}
+	*objects = converted;
+}
+
+// The area an object paints in canvas space, with strokes widened to their width
+fn filled_area(object: &RenderObject, time: f64) -> Option<BezPath> {
+	match &object.drawable {
+		Drawable::PathShape(shape) => {
+			let mut lines = BezPath::new();
+			shape.path.flatten(FLATTEN_TOLERANCE, |el| lines.push(el));
+			let mut area = if shape.filled {
+				lines
+			} else {
+				stroke_outline(&lines, shape.width)
+			};
+			area.apply_affine(object.transform);
+			Some(area)
+		}
+		// Lines cover whatever the paths they convert to cover
+		_ => {
+			let mut area = BezPath::new();
+			// Shapes are combined at full detail, and only the result is simplified
+			for path in to_paths(object, time, Simplification::default())? {
+				for el in filled_area(&path, time)?.elements() {
+					area.push(*el);
+				}
+			}
+			Some(area)
+		}
+	}
+}
+
+// The area a stroked path made of lines covers, as closed outlines around each subpath. Open
+// subpaths give one outline, closed ones an outline on either side
+fn stroke_outline(lines: &BezPath, width: f64) -> BezPath {
+	let half_width = width / 2.0;
+	let mut outline = BezPath::new();
+	for (mut points, closed) in simplify::polylines(lines) {
+		points.dedup();
+		// Closed subpaths end with their start point, which the corner at the start covers
+		if closed {
+			points.pop();
+		}
+		let count = points.len();
+		if count < 2 {
+			continue;
+		}
+		let offsets: Vec<Vec2> = (0..count)
+			.map(|i| {
+				let wrap = Some(points[0] - points[count - 1]).filter(|_| closed);
+				let before = if i > 0 {
+					Some(points[i] - points[i - 1])
+				} else {
+					wrap
+				};
+				let after = if i + 1 < count {
+					Some(points[i + 1] - points[i])
+				} else {
+					wrap
+				};
+				corner_offset(before, after) * half_width
+			})
+			.collect();
+		let left = points.iter().zip(&offsets).map(|(p, offset)| *p + *offset);
+		let right = points.iter().zip(&offsets).map(|(p, offset)| *p - *offset);
+		if closed {
+			push_ring(&mut outline, left);
+			push_ring(&mut outline, right.rev());
+		} else {
+			push_ring(&mut outline, left.chain(right.rev()));
+		}
+	}
+	outline
+}
+
+// Direction from a point of a stroke to its left edge, in half widths, given the segments either
+// side of it
+fn corner_offset(before: Option<Vec2>, after: Option<Vec2>) -> Vec2 {
+	let normal = |d: Vec2| Vec2::new(d.y, -d.x).normalize();
+	match (before, after) {
+		(Some(before), Some(after)) => {
+			let (before, after) = (normal(before), normal(after));
+			let sum = before + after;
+			// The path turns straight back on itself
+			if sum.hypot() < 1e-9 {
+				return before;
+			}
+			let direction = sum.normalize();
+			direction / direction.dot(before).max(1.0 / MITER_LIMIT)
+		}
+		(Some(d), None) | (None, Some(d)) => normal(d),
+		(None, None) => Vec2::ZERO,
+	}
+}
+
+fn push_ring(path: &mut BezPath, points: impl Iterator<Item = Point>) {
+	for (i, p) in points.enumerate() {
+		if i == 0 {
+			path.move_to(p);
+		} else {
+			path.line_to(p);
+		}
+	}
+	path.close_path();
+}
+
+// The look an object has as a path, which the result of combining it carries on
+fn path_style(object: &RenderObject, time: f64) -> PathShape {
+	let paths = to_paths(object, time, Simplification::default());
+	let drawable = paths
+		.as_ref()
+		.and_then(|paths| paths.first())
+		.map_or(&object.drawable, |path| &path.drawable);
+	match drawable {
+		Drawable::PathShape(shape) => shape.clone(),
+		_ => PathShape::filled(BezPath::new()),
+	}
+}
+
+/// Combine the areas the selected objects cover into a single filled path.
+///
+/// Strokes count as the area they paint. Shapes are combined in drawing order, and the result
+/// takes the place of the bottommost one along with its opacity and width.
+pub fn combine_selected(
+	objects: &mut Vector<RenderObject>,
+	op: BooleanOp,
+	time: f64,
+	simplification: Simplification,
+) {
+	let areas: Vec<(usize, BezPath)> = objects
+		.iter()
+		.enumerate()
+		.filter(|(_, object)| object.is_selected())
+		.filter_map(|(i, object)| Some((i, filled_area(object, time)?)))
+		.collect();
+	if areas.len() < 2 {
+		return;
+	}
+	let result = areas[1..]
+		.iter()
+		.fold(areas[0].1.clone(), |acc, (_, area)| {
+			boolean::combine(&acc, area, op)
+		});
+
+	let result = simplification.apply(&result);
+	// Nothing is left, such as separate shapes intersected, so rather than delete every operand
+	// the scene is left as it was
+	if result.elements().is_empty() {
+		return;
+	}
+
+	let first = areas[0].0;
+	let used: Vec<usize> = areas.iter().map(|(i, _)| *i).collect();
+	let mut combined = Vector::new();
+	for (i, object) in objects.iter().enumerate() {
+		if i == first {
+			let mut shape = RenderObject::new(Drawable::PathShape(PathShape {
+				path: Arc::new(result.clone()),
+				filled: true,
+				..path_style(object, time)
+			}));
+			shape.select();
+			combined.push_back(shape);
+		} else if !used.contains(&i) {
+			combined.push_back(object.clone());
+		}
+	}
+	*objects = combined;
+}
+
+#[cfg(test)]
+mod tests {
+	use druid::kurbo::{PathEl, Shape};
+
+	use super::*;
+
+	fn polyline(points: &[(f64, f64)], closed: bool) -> BezPath {
+		let mut path = BezPath::new();
+		path.move_to(points[0]);
+		for p in &points[1..] {
+			path.line_to(*p);
+		}
+		if closed {
+			path.close_path();
+		}
+		path
+	}
+
+	#[test]
+	fn open_stroke_outline() {
+		let outline = stroke_outline(
+			&polyline(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)], false),
+			2.0,
+		);
+		// Both arms, square at the ends and mitred at the corner
+		assert!((outline.area().abs() - 40.0).abs() < 1e-9);
+	}
+
+	#[test]
+	fn closed_stroke_outline_has_a_hole() {
+		let square = polyline(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)], true);
+		let outline = stroke_outline(&square, 2.0);
+		assert!((outline.area().abs() - 80.0).abs() < 1e-9);
+		let loops = outline
+			.elements()
+			.iter()
+			.filter(|el| matches!(el, PathEl::ClosePath))
+			.count();
+		assert_eq!(loops, 2);
+	}
+
+	#[test]
+	fn strokes_are_combined_by_the_area_they_paint() {
+		let stroke = |points: &[(f64, f64)], opacity: f64| {
+			let mut object = RenderObject::new(Drawable::PathShape(
+				PathShape::stroked(polyline(points, false), 2.0).with_opacity(opacity),
+			));
+			object.select();
+			object
+		};
+		let mut objects = Vector::new();
+		objects.push_back(stroke(&[(0.0, 5.0), (10.0, 5.0)], 0.5));
+		objects.push_back(stroke(&[(5.0, 0.0), (5.0, 10.0)], 1.0));
+		combine_selected(
+			&mut objects,
+			BooleanOp::Union,
+			0.0,
+			Simplification::default(),
+		);
+		assert_eq!(objects.len(), 1);
+		match &objects[0].drawable {
+			Drawable::PathShape(shape) => {
+				assert!(shape.filled);
+				assert_eq!(shape.opacity, 0.5);
+				// Two 10 by 2 bars crossing over a 2 by 2 square
+				assert!((shape.path.area().abs() - 36.0).abs() < 1e-6);
+			}
+			_ => panic!("the result is not a path"),
+		}
+	}
+}
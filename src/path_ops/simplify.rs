@@ -0,0 +1,493 @@
+use druid::kurbo::{BezPath, CubicBez, ParamCurve, ParamCurveDeriv, PathEl, Point, Vec2};
+use druid::Env;
+
+use crate::preferences::{FIT_CURVES, SIMPLIFY_TOLERANCE};
+
+// Curves are only fitted through runs of at least this many points, shorter runs stay as lines
+const MIN_FIT_POINTS: usize = 4;
+// Times the sample positions along a curve are refined before giving up and splitting it
+const REFINE_STEPS: usize = 3;
+
+/// How baked and exported paths are reduced, the default leaves them alone
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Simplification {
+	// Furthest any point of the result may be from the original path, zero leaves paths alone
+	pub tolerance: f64,
+	// Replace runs of lines with cubic Béziers, rather than only dropping points
+	pub fit_curves: bool,
+}
+
+impl Simplification {
+	/// The simplification set in the preferences
+	pub fn from_env(env: &Env) -> Self {
+		Self {
+			tolerance: env.get(SIMPLIFY_TOLERANCE),
+			fit_curves: env.get(FIT_CURVES),
+		}
+	}
+
+	/// A path made of fewer segments that stays within tolerance of the original.
+	///
+	/// Paths that already contain curves are left as they are.
+	pub fn apply(&self, path: &BezPath) -> BezPath {
+		if !self.applies_to(path) {
+			return path.clone();
+		}
+		let fit_tolerance = Some(self.tolerance).filter(|_| self.fit_curves);
+		let mut result = BezPath::new();
+		for (points, closed) in polylines(path) {
+			let keep = kept(&points, self.tolerance);
+			push_polyline(&mut result, &points, &keep, closed, fit_tolerance);
+		}
+		result
+	}
+
+	/// The frames of an animation simplified alike, so that they keep the same commands and can
+	/// still be interpolated between.
+	///
+	/// A point is kept in every frame if any frame needs it. Curves are only fitted to a single
+	/// frame, as each frame would be split into curves differently.
+	pub fn apply_to_frames(&self, frames: &[BezPath]) -> Vec<BezPath> {
+		if frames.len() == 1 {
+			return vec![self.apply(&frames[0])];
+		}
+		let shapes: Vec<Vec<(Vec<Point>, bool)>> = frames.iter().map(polylines).collect();
+		let alike = shapes.windows(2).all(|pair| {
+			pair[0].len() == pair[1].len()
+				&& pair[0]
+					.iter()
+					.zip(&pair[1])
+					.all(|(a, b)| a.0.len() == b.0.len() && a.1 == b.1)
+		});
+		// Frames with different commands can't be interpolated anyway, so are left alone
+		if frames.is_empty() || !alike || !frames.iter().all(|f| self.applies_to(f)) {
+			return frames.to_vec();
+		}
+		let keeps: Vec<Vec<bool>> = (0..shapes[0].len())
+			.map(|i| {
+				let mut keep = vec![false; shapes[0][i].0.len()];
+				for shape in &shapes {
+					for (keep, kept) in keep.iter_mut().zip(kept(&shape[i].0, self.tolerance)) {
+						*keep |= kept;
+					}
+				}
+				keep
+			})
+			.collect();
+		shapes
+			.iter()
+			.map(|shape| {
+				let mut result = BezPath::new();
+				for ((points, closed), keep) in shape.iter().zip(&keeps) {
+					push_polyline(&mut result, points, keep, *closed, None);
+				}
+				result
+			})
+			.collect()
+	}
+
+	// Whether simplifying is turned on and the path is made only of lines
+	fn applies_to(&self, path: &BezPath) -> bool {
+		let has_curves = path
+			.elements()
+			.iter()
+			.any(|el| matches!(el, PathEl::QuadTo(..) | PathEl::CurveTo(..)));
+		self.tolerance > 0.0 && !has_curves
+	}
+}
+
+// Append the kept points of a polyline to the path, as lines or as curves fitted within the
+// given tolerance
+fn push_polyline(
+	path: &mut BezPath,
+	points: &[Point],
+	keep: &[bool],
+	closed: bool,
+	fit_tolerance: Option<f64>,
+) {
+	let knots: Vec<usize> = (0..points.len()).filter(|&i| keep[i]).collect();
+	path.move_to(points[0]);
+	match fit_tolerance {
+		Some(tolerance) if knots.len() >= MIN_FIT_POINTS => {
+			fit_curves(points, &knots, tolerance, path)
+		}
+		_ => {
+			for &i in &knots[1..] {
+				path.line_to(points[i]);
+			}
+		}
+	}
+	if closed {
+		path.close_path();
+	}
+}
+
+// Every subpath of a path made of lines as its points, and whether it is closed
+pub(super) fn polylines(path: &BezPath) -> Vec<(Vec<Point>, bool)> {
+	let mut polylines = Vec::new();
+	let mut points: Vec<Point> = Vec::new();
+	for el in path.elements() {
+		match *el {
+			PathEl::MoveTo(p) => {
+				if !points.is_empty() {
+					polylines.push((std::mem::take(&mut points), false));
+				}
+				points.push(p);
+			}
+			PathEl::LineTo(p) | PathEl::QuadTo(_, p) | PathEl::CurveTo(_, _, p) => points.push(p),
+			PathEl::ClosePath => {
+				if !points.is_empty() {
+					// The closing segment is implied, so returning to the start point is redundant
+					if points.len() > 1 && points[0] == points[points.len() - 1] {
+						points.pop();
+					}
+					let start = points[0];
+					points.push(start);
+					polylines.push((std::mem::take(&mut points), true));
+				}
+			}
+		}
+	}
+	if !points.is_empty() {
+		polylines.push((points, false));
+	}
+	polylines
+}
+
+/// Drop points from a polyline while keeping it within tolerance of the original, using the
+/// Ramer–Douglas–Peucker algorithm
+pub fn simplify(points: &[Point], tolerance: f64) -> Vec<Point> {
+	points
+		.iter()
+		.zip(kept(points, tolerance))
+		.filter(|(_, keep)| *keep)
+		.map(|(p, _)| *p)
+		.collect()
+}
+
+/// Which points of a polyline [`simplify`] keeps
+pub fn kept(points: &[Point], tolerance: f64) -> Vec<bool> {
+	if points.len() < 3 {
+		return vec![true; points.len()];
+	}
+	let mut keep = vec![false; points.len()];
+	keep[0] = true;
+	keep[points.len() - 1] = true;
+	// Explicit stack rather than recursion, as baked lines can have thousands of points
+	let mut spans = vec![(0, points.len() - 1)];
+	while let Some((first, last)) = spans.pop() {
+		let furthest = (first + 1..last)
+			.map(|i| {
+				(
+					i,
+					distance_to_segment(points[i], points[first], points[last]),
+				)
+			})
+			.max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+		if let Some((i, distance)) = furthest {
+			if distance > tolerance {
+				keep[i] = true;
+				spans.push((first, i));
+				spans.push((i, last));
+			}
+		}
+	}
+	keep
+}
+
+fn distance_to_segment(p: Point, a: Point, b: Point) -> f64 {
+	let ab = b - a;
+	let length_sq = ab.hypot2();
+	if length_sq == 0.0 {
+		return p.distance(a);
+	}
+	let t = ((p - a).dot(ab) / length_sq).max(0.0).min(1.0);
+	p.distance(a + ab * t)
+}
+
+// Append cubic Béziers that pass within tolerance of every point, after the first, to the path.
+//
+// This follows Schneider's algorithm from Graphics Gems: fit one curve by least squares, and
+// if it strays too far split the points where it is worst and fit each half. Curves only start
+// and end at the knots, the points kept by simplify, but are fitted to and checked against all
+// of the points between them.
+fn fit_curves(points: &[Point], knots: &[usize], tolerance: f64, path: &mut BezPath) {
+	let last = knots.len() - 1;
+	let start_tangent = tangent(points[knots[0]], points[knots[1]]);
+	let end_tangent = tangent(points[knots[last]], points[knots[last - 1]]);
+	fit_span(points, knots, start_tangent, end_tangent, tolerance, path);
+}
+
+fn fit_span(
+	points: &[Point],
+	knots: &[usize],
+	start_tangent: Vec2,
+	end_tangent: Vec2,
+	tolerance: f64,
+	path: &mut BezPath,
+) {
+	let last = knots.len() - 1;
+	// Simplifying already left every point between two neighbouring knots near the line
+	if knots.len() == 2 {
+		path.line_to(points[knots[1]]);
+		return;
+	}
+
+	let span = &points[knots[0]..=knots[last]];
+	let mut params = chord_lengths(span);
+	let mut curve = fit_cubic(span, &params, start_tangent, end_tangent);
+	let (mut error, mut worst) = max_error(span, &params, &curve);
+	for _ in 0..REFINE_STEPS {
+		if error <= tolerance {
+			break;
+		}
+		params = refine(span, &params, &curve);
+		curve = fit_cubic(span, &params, start_tangent, end_tangent);
+		let (new_error, new_worst) = max_error(span, &params, &curve);
+		error = new_error;
+		worst = new_worst;
+	}
+	if error <= tolerance {
+		path.curve_to(curve.p1, curve.p2, curve.p3);
+		return;
+	}
+
+	// Split at the inner knot nearest the worst point. The curve is smooth through it, so both
+	// halves share its tangent
+	let worst = knots[0] + worst;
+	let split = (1..last)
+		.min_by_key(|&k| (knots[k] as isize - worst as isize).abs())
+		.unwrap_or(last / 2);
+	let center_tangent = tangent(points[knots[split - 1]], points[knots[split + 1]]);
+	fit_span(
+		points,
+		&knots[..=split],
+		start_tangent,
+		center_tangent * -1.0,
+		tolerance,
+		path,
+	);
+	fit_span(
+		points,
+		&knots[split..],
+		center_tangent,
+		end_tangent,
+		tolerance,
+		path,
+	);
+}
+
+fn tangent(from: Point, to: Point) -> Vec2 {
+	let dir = to - from;
+	if dir.hypot() == 0.0 {
+		Vec2::ZERO
+	} else {
+		dir.normalize()
+	}
+}
+
+// Positions in [0, 1] for each point, in proportion to the distance along the polyline
+fn chord_lengths(points: &[Point]) -> Vec<f64> {
+	let mut params = vec![0.0];
+	for pair in points.windows(2) {
+		params.push(params[params.len() - 1] + pair[0].distance(pair[1]));
+	}
+	let total = params[params.len() - 1];
+	if total > 0.0 {
+		params.iter_mut().for_each(|t| *t /= total);
+	}
+	params
+}
+
+// The cubic with the given end tangents that best fits the points, by least squares on the
+// lengths of its control arms
+fn fit_cubic(points: &[Point], params: &[f64], start_tangent: Vec2, end_tangent: Vec2) -> CubicBez {
+	let first = points[0];
+	let last = points[points.len() - 1];
+	let mut c = [[0.0; 2]; 2];
+	let mut x = [0.0; 2];
+	for (p, &t) in points.iter().zip(params) {
+		let mt = 1.0 - t;
+		let b0 = mt.powi(3);
+		let b1 = 3.0 * t * mt.powi(2);
+		let b2 = 3.0 * t.powi(2) * mt;
+		let b3 = t.powi(3);
+		let a1 = start_tangent * b1;
+		let a2 = end_tangent * b2;
+		c[0][0] += a1.dot(a1);
+		c[0][1] += a1.dot(a2);
+		c[1][1] += a2.dot(a2);
+		let rest = p.to_vec2() - (first.to_vec2() * (b0 + b1) + last.to_vec2() * (b2 + b3));
+		x[0] += a1.dot(rest);
+		x[1] += a2.dot(rest);
+	}
+	c[1][0] = c[0][1];
+
+	let det = c[0][0] * c[1][1] - c[0][1] * c[1][0];
+	let chord = first.distance(last);
+	let (mut alpha1, mut alpha2) = if det.abs() > 1e-12 {
+		(
+			(x[0] * c[1][1] - x[1] * c[0][1]) / det,
+			(c[0][0] * x[1] - c[1][0] * x[0]) / det,
+		)
+	} else {
+		(chord / 3.0, chord / 3.0)
+	};
+	// Arms that are backwards or vanishingly short give loops and cusps, fall back to a third
+	// of the chord which is always well behaved
+	let min_arm = chord * 1e-6;
+	if alpha1 < min_arm || alpha2 < min_arm {
+		alpha1 = chord / 3.0;
+		alpha2 = chord / 3.0;
+	}
+	CubicBez::new(
+		first,
+		first + start_tangent * alpha1,
+		last + end_tangent * alpha2,
+		last,
+	)
+}
+
+// The furthest any point is from where the curve puts it, and the index of that point
+fn max_error(points: &[Point], params: &[f64], curve: &CubicBez) -> (f64, usize) {
+	points
+		.iter()
+		.zip(params)
+		.enumerate()
+		.map(|(i, (p, &t))| (curve.eval(t).distance(*p), i))
+		.fold((0.0, points.len() / 2), |worst, (error, i)| {
+			if error > worst.0 {
+				(error, i)
+			} else {
+				worst
+			}
+		})
+}
+
+// Move each position along the curve closer to the nearest point to its sample, by one step of
+// Newton's method
+fn refine(points: &[Point], params: &[f64], curve: &CubicBez) -> Vec<f64> {
+	let d1 = curve.deriv();
+	let d2 = d1.deriv();
+	points
+		.iter()
+		.zip(params)
+		.map(|(p, &t)| {
+			let offset = curve.eval(t) - *p;
+			let first = d1.eval(t).to_vec2();
+			let second = d2.eval(t).to_vec2();
+			let denominator = first.dot(first) + offset.dot(second);
+			if denominator.abs() < 1e-12 {
+				t
+			} else {
+				(t - offset.dot(first) / denominator).max(0.0).min(1.0)
+			}
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use druid::kurbo::PathSeg;
+
+	fn polyline(points: &[Point]) -> BezPath {
+		let mut path = BezPath::new();
+		path.move_to(points[0]);
+		for p in &points[1..] {
+			path.line_to(*p);
+		}
+		path
+	}
+
+	fn wave(count: usize, phase: f64) -> Vec<Point> {
+		(0..count)
+			.map(|i| {
+				let x = i as f64 * 0.5;
+				Point::new(x, 20.0 * (x / 15.0 + phase).sin())
+			})
+			.collect()
+	}
+
+	// How far a point is from anywhere on the path, by sampling each segment finely
+	fn distance_to_path(path: &BezPath, p: Point) -> f64 {
+		path.segments()
+			.flat_map(|seg: PathSeg| (0..=200).map(move |i| seg.eval(i as f64 / 200.0)))
+			.map(|q| q.distance(p))
+			.fold(f64::INFINITY, f64::min)
+	}
+
+	#[test]
+	fn simplify_keeps_only_corners() {
+		let points = [
+			Point::new(0.0, 0.0),
+			Point::new(1.0, 0.0),
+			Point::new(2.0, 0.0),
+			Point::new(3.0, 0.0),
+			Point::new(3.0, 1.0),
+			Point::new(3.0, 2.0),
+		];
+		assert_eq!(
+			simplify(&points, 0.1),
+			vec![points[0], points[3], points[5]]
+		);
+	}
+
+	#[test]
+	fn simplify_drops_noise_within_tolerance() {
+		let points: Vec<Point> = (0..50)
+			.map(|i| Point::new(i as f64, if i % 2 == 0 { 0.05 } else { -0.05 }))
+			.collect();
+		assert_eq!(simplify(&points, 0.1).len(), 2);
+		assert!(simplify(&points, 0.01).len() > 40);
+	}
+
+	#[test]
+	fn lines_stay_within_tolerance() {
+		let points = wave(200, 0.0);
+		let simplification = Simplification {
+			tolerance: 0.2,
+			fit_curves: false,
+		};
+		let result = simplification.apply(&polyline(&points));
+		assert!(result.elements().len() < points.len() / 4);
+		for p in &points {
+			assert!(distance_to_path(&result, *p) <= 0.2 + 1e-6);
+		}
+	}
+
+	#[test]
+	fn fitted_curves_stay_within_tolerance_of_every_point() {
+		let points = wave(200, 0.0);
+		let simplification = Simplification {
+			tolerance: 0.2,
+			fit_curves: true,
+		};
+		let result = simplification.apply(&polyline(&points));
+		assert!(result
+			.elements()
+			.iter()
+			.any(|el| matches!(el, PathEl::CurveTo(..))));
+		assert!(result.elements().len() < points.len() / 10);
+		for p in &points {
+			// Slightly more than the tolerance, as the distance is only sampled
+			assert!(distance_to_path(&result, *p) <= 0.25);
+		}
+	}
+
+	#[test]
+	fn frames_keep_the_same_commands() {
+		let simplification = Simplification {
+			tolerance: 0.2,
+			fit_curves: true,
+		};
+		let frames: Vec<BezPath> = (0..3).map(|i| polyline(&wave(200, i as f64))).collect();
+		let result = simplification.apply_to_frames(&frames);
+		let lengths: Vec<usize> = result.iter().map(|f| f.elements().len()).collect();
+		assert!(lengths.iter().all(|&len| len == lengths[0] && len < 200));
+		assert!(result.iter().all(|f| f
+			.elements()
+			.iter()
+			.all(|el| !matches!(el, PathEl::CurveTo(..)))));
+	}
+}
@@ -0,0 +1,178 @@
+//! User preferences for behaviour that isn't tied to a particular tool.
+//!
+//! Preferences are published through the `Env` under the keys below, so that
+//! widgets and drawables read them at paint and event time and pick up changes live.
+use druid::{Color, Data, Env, Key, Lens};
+
+use crate::config::{self, ConfigFile};
+
+const PREFERENCES_FILE: &str = "preferences.conf";
+
+pub const BACKGROUND_COLOR: Key<Color> = Key::new("lipuma.background-color");
+/// The color selected objects are drawn in
+pub const SELECTION_COLOR: Key<Color> = Key::new("lipuma.selection-color");
+/// Length of the dashes in the selection rectangle
+pub const SELECTION_DASH: Key<f64> = Key::new("lipuma.selection-dash");
+/// Length of the gaps in the selection rectangle
+pub const SELECTION_GAP: Key<f64> = Key::new("lipuma.selection-gap");
+/// Tolerance used when flattening objects to test what the selection rectangle touches
+pub const COLLISION_TOLERANCE: Key<f64> = Key::new("lipuma.collision-tolerance");
+/// Zoom factor applied by each press of the zoom keys
+pub const ZOOM_STEP: Key<f64> = Key::new("lipuma.zoom-step");
+/// Natural log of the zoom factor applied per pixel of wheel movement
+pub const WHEEL_ZOOM_SENSITIVITY: Key<f64> = Key::new("lipuma.wheel-zoom-sensitivity");
+/// Furthest a converted or exported path may stray from the original when simplified
+pub const SIMPLIFY_TOLERANCE: Key<f64> = Key::new("lipuma.simplify-tolerance");
+/// Whether simplified paths are smoothed into curves
+pub const FIT_CURVES: Key<bool> = Key::new("lipuma.fit-curves");
+
+// Ranges of the preferences dialog's sliders as (min, max), which values loaded from the file
+// are clamped into as some, like a zoom step of zero, would break the canvas
+pub const SELECTION_DASH_RANGE: (f64, f64) = (0.5, 20.0);
+pub const SELECTION_GAP_RANGE: (f64, f64) = (0.5, 20.0);
+pub const COLLISION_TOLERANCE_RANGE: (f64, f64) = (0.1, 20.0);
+pub const ZOOM_STEP_RANGE: (f64, f64) = (1.01, 2.0);
+pub const WHEEL_ZOOM_SENSITIVITY_RANGE: (f64, f64) = (0.0005, 0.01);
+pub const SIMPLIFY_TOLERANCE_RANGE: (f64, f64) = (0.0, 2.0);
+
+#[derive(Data, Clone, Debug, PartialEq, Lens)]
+pub struct Preferences {
+	pub background: Color,
+	pub selection_color: Color,
+	pub selection_dash: f64,
+	pub selection_gap: f64,
+	pub collision_tolerance: f64,
+	pub zoom_step: f64,
+	pub wheel_zoom_sensitivity: f64,
+	pub simplify_tolerance: f64,
+	pub fit_curves: bool,
+}
+
+impl Preferences {
+	/// Load the preferences file, using the defaults for anything it doesn't set
+	pub fn load() -> Self {
+		let mut preferences = Self::default();
+		if let Some(file) = config::read(PREFERENCES_FILE) {
+			for (_, entries) in &file.sections {
+				for (key, value) in entries {
+					if preferences.set(key, value).is_none() {
+						log::warn!("Bad preference {} = {}", key, value);
+					}
+				}
+			}
+		}
+		preferences
+	}
+
+	pub fn save(&self) {
+		let entries = vec![
+			("background".to_string(), color_to_hex(&self.background)),
+			(
+				"selection_color".to_string(),
+				color_to_hex(&self.selection_color),
+			),
+			(
+				"selection_dash".to_string(),
+				self.selection_dash.to_string(),
+			),
+			("selection_gap".to_string(), self.selection_gap.to_string()),
+			(
+				"collision_tolerance".to_string(),
+				self.collision_tolerance.to_string(),
+			),
+			("zoom_step".to_string(), self.zoom_step.to_string()),
+			(
+				"wheel_zoom_sensitivity".to_string(),
+				self.wheel_zoom_sensitivity.to_string(),
+			),
+			(
+				"simplify_tolerance".to_string(),
+				self.simplify_tolerance.to_string(),
+			),
+			("fit_curves".to_string(), self.fit_curves.to_string()),
+		];
+		let mut file = ConfigFile::default();
+		file.add_section("", entries);
+		if let Err(e) = config::write(PREFERENCES_FILE, &file) {
+			log::error!("Failed to save preferences: {}", e);
+		}
+	}
+
+	// Set a preference from its text in the file, returning None if the key or value is invalid.
+	// Numbers outside the dialog's ranges are clamped into them
+	fn set(&mut self, key: &str, value: &str) -> Option<()> {
+		match key {
+			"background" => self.background = color_from_hex(value)?,
+			"selection_color" => self.selection_color = color_from_hex(value)?,
+			"selection_dash" => self.selection_dash = number(value, SELECTION_DASH_RANGE)?,
+			"selection_gap" => self.selection_gap = number(value, SELECTION_GAP_RANGE)?,
+			"collision_tolerance" => {
+				self.collision_tolerance = number(value, COLLISION_TOLERANCE_RANGE)?
+			}
+			"zoom_step" => self.zoom_step = number(value, ZOOM_STEP_RANGE)?,
+			"wheel_zoom_sensitivity" => {
+				self.wheel_zoom_sensitivity = number(value, WHEEL_ZOOM_SENSITIVITY_RANGE)?
+			}
+			"simplify_tolerance" => {
+				self.simplify_tolerance = number(value, SIMPLIFY_TOLERANCE_RANGE)?
+			}
+			"fit_curves" => self.fit_curves = value.parse().ok()?,
+			_ => return None,
+		}
+		Some(())
+	}
+
+	/// Publish the preferences into an Env
+	pub fn apply(&self, env: &mut Env) {
+		env.set(BACKGROUND_COLOR, self.background.clone());
+		env.set(SELECTION_COLOR, self.selection_color.clone());
+		env.set(SELECTION_DASH, self.selection_dash);
+		env.set(SELECTION_GAP, self.selection_gap);
+		env.set(COLLISION_TOLERANCE, self.collision_tolerance);
+		env.set(ZOOM_STEP, self.zoom_step);
+		env.set(WHEEL_ZOOM_SENSITIVITY, self.wheel_zoom_sensitivity);
+		env.set(SIMPLIFY_TOLERANCE, self.simplify_tolerance);
+		env.set(FIT_CURVES, self.fit_curves);
+	}
+}
+
+impl Default for Preferences {
+	fn default() -> Self {
+		Self {
+			background: Color::WHITE,
+			selection_color: Color::RED,
+			selection_dash: 3.0,
+			selection_gap: 3.0,
+			collision_tolerance: 5.0,
+			zoom_step: 1.25,
+			wheel_zoom_sensitivity: 0.002,
+			simplify_tolerance: 0.25,
+			fit_curves: true,
+		}
+	}
+}
+
+// A number from the file clamped into a range, None if it isn't a number
+fn number(value: &str, (min, max): (f64, f64)) -> Option<f64> {
+	let number: f64 = value.parse().ok()?;
+	if number.is_nan() {
+		None
+	} else {
+		Some(number.clamp(min, max))
+	}
+}
+
+fn color_to_hex(color: &Color) -> String {
+	format!("#{:08x}", color.as_rgba_u32())
+}
+
+// Parses #rrggbb or #rrggbbaa
+fn color_from_hex(text: &str) -> Option<Color> {
+	let hex = text.trim().trim_start_matches('#');
+	let value = u32::from_str_radix(hex, 16).ok()?;
+	match hex.len() {
+		6 => Some(Color::from_rgba32_u32((value << 8) | 0xff)),
+		8 => Some(Color::from_rgba32_u32(value)),
+		_ => None,
+	}
+}
@@ -0,0 +1,20 @@
+use druid::{Data, Key, Lens};
+
+/// The current time of the scene's animation in seconds, made available to drawables when painting
+pub const ANIMATION_TIME: Key<f64> = Key::new("lipuma.animation-time");
+
+#[derive(Data, Clone, Copy, Debug, Default, PartialEq, Lens)]
+pub struct Animation {
+	// Noise offset travelled per second, making the line crawl along itself
+	pub crawl_speed: f64,
+	// How many times per second the line morphs into a new noise pattern
+	pub shimmer_speed: f64,
+	// The time in seconds of the frame the line is showing
+	pub time: f64,
+}
+
+impl Animation {
+	pub fn is_animated(&self) -> bool {
+		self.crawl_speed != 0.0 || self.shimmer_speed != 0.0
+	}
+}
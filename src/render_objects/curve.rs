@@ -0,0 +1,37 @@
+use druid::Data;
+
+/// The number of evenly spaced control points in a Curve
+pub const CURVE_POINTS: usize = 9;
+
+/// A user editable curve over [0, 1], stored as evenly spaced samples
+/// and linearly interpolated between them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Curve(pub [f64; CURVE_POINTS]);
+
+impl Curve {
+	pub fn flat(value: f64) -> Self {
+		Self([value; CURVE_POINTS])
+	}
+
+	pub fn sample(&self, x: f64) -> f64 {
+		let scaled = x.clamp(0.0, 1.0) * (CURVE_POINTS - 1) as f64;
+		let floor = scaled.floor() as usize;
+		if floor >= CURVE_POINTS - 1 {
+			return self.0[CURVE_POINTS - 1];
+		}
+		let fract = scaled - floor as f64;
+		self.0[floor] + (self.0[floor + 1] - self.0[floor]) * fract
+	}
+}
+
+impl Default for Curve {
+	fn default() -> Self {
+		Self::flat(1.0)
+	}
+}
+
+impl Data for Curve {
+	fn same(&self, other: &Self) -> bool {
+		self == other
+	}
+}
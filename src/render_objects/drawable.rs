@@ -1,29 +1,84 @@
 use super::fractal_line::FractalLine;
+use super::multi_strand_line::MultiStrandLine;
+use super::path_shape::PathShape;
 use super::selection_rect::SelectionRect;
-use druid::{kurbo::BezPath, Data, Rect};
+use super::snap_target::SnapTarget;
+use druid::{kurbo::BezPath, Data, Point, Rect, Selector};
 use trait_enum::trait_enum;
 
 use super::RenderObject;
 
+/// Sent to a drawable's event method when the pointer moves over it
+pub const POINTER_ENTERED: Selector = Selector::new("lipuma.pointer-entered");
+/// Sent to a drawable's event method when the pointer moves off it
+pub const POINTER_LEFT: Selector = Selector::new("lipuma.pointer-left");
+
 pub trait Drawable {
 	#[allow(non_snake_case)]
 	fn AABB(&self) -> Rect;
 	fn fine_collision_shape(&self, tolerance: f64) -> BezPath;
 
+	// The tightest box around what is painted, for exports and zooming to fit. AABB only has to
+	// contain it, as it is worked out far more often for repainting and hit testing
+	fn exact_bounds(&self) -> Rect {
+		self.AABB()
+	}
+
+	// Whether the drawable changes over time and needs repainting as the animation plays
+	fn is_animated(&self) -> bool {
+		false
+	}
+
+	// Points of interest on the drawable that tools can snap to
+	fn snap_points(&self) -> Vec<SnapTarget> {
+		Vec::new()
+	}
+
+	// Points that can be dragged to reshape the drawable, such as the ends of a line
+	fn handles(&self) -> Vec<Point> {
+		Vec::new()
+	}
+
+	// Move the handle at an index into handles() to a new position
+	fn move_handle(&mut self, _index: usize, _to: Point) {}
+
+	// Handle a pointer event in the drawable's own coordinate space, see POINTER_ENTERED.
+	//
+	// Setting the event as handled keeps it from the active tool, and handling a MouseDown
+	// captures the pointer so that the drawable receives every mouse event until release.
 	fn event(
 		&mut self,
-		ctx: &mut druid::EventCtx,
-		event: &druid::Event,
-		env: &druid::Env,
-		sctx: &RenderObject,
-	);
+		_ctx: &mut druid::EventCtx,
+		_event: &druid::Event,
+		_env: &druid::Env,
+		_sctx: &RenderObject,
+	) {
+	}
+
 	fn paint(&self, ctx: &mut druid::PaintCtx, env: &druid::Env, sctx: &RenderObject);
 }
 
 trait_enum! {
-	#[derive(Data, Clone, Copy)]
+	#[derive(Data, Clone)]
 	pub enum DrawableObj: Drawable {
 		FractalLine,
-		SelectionRect
+		MultiStrandLine,
+		SelectionRect,
+		PathShape
+	}
+}
+
+impl DrawableObj {
+	/// The drawable as it looks at an animation time in seconds, drawables that don't animate
+	/// are returned unchanged
+	pub fn at_time(&self, time: f64) -> Self {
+		match self {
+			DrawableObj::FractalLine(line) => DrawableObj::FractalLine(line.at_time(time)),
+			DrawableObj::MultiStrandLine(multi) => DrawableObj::MultiStrandLine(MultiStrandLine {
+				line: multi.line.at_time(time),
+				..*multi
+			}),
+			other => other.clone(),
+		}
 	}
 }
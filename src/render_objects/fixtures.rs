@@ -0,0 +1,14 @@
+//! Objects shared by tests across the crate.
+use druid::{kurbo::Shape, Affine, Rect};
+
+use super::{Drawable, PathShape, RenderObject};
+
+/// A filled square from the origin to (10, 10), which has no stroke to widen its bounds
+pub fn square(transform: Affine) -> RenderObject {
+	RenderObject {
+		transform,
+		..RenderObject::new(Drawable::PathShape(PathShape::filled(
+			Rect::new(0.0, 0.0, 10.0, 10.0).to_path(0.1),
+		)))
+	}
+}
@@ -3,13 +3,65 @@ use druid::{
 	Color, Data, Point, Rect, RenderContext, Vec2,
 };
 
-use super::{drawable::Drawable, RenderObject};
+use super::{
+	animation::{Animation, ANIMATION_TIME},
+	drawable::Drawable,
+	path_shape::PathShape,
+	snap_target::{SnapKind, SnapTarget},
+	stroke::{Stroke, StrokeMode},
+	taper::Taper,
+	RenderObject,
+};
+use crate::noise::{base_noise::get_hash, NoiseObj, OctaveStyle};
+use crate::preferences::SELECTION_COLOR;
 
-#[derive(Data, Clone, Copy, Debug, Default, PartialEq)]
+/// The largest number of weighted octaves a FractalNoise can be configured to use. Its octave
+/// count also includes the base wavelength, so can be one more than this
+pub const MAX_OCTAVES: usize = 8;
+
+// Tolerance the line is flattened with when painted, and so when its bounds are measured
+const PAINT_TOLERANCE: f64 = 0.1;
+// Mitred corners of a stroke can reach this many half widths from the path, piet's default limit
+const MITER_LIMIT: f64 = 10.0;
+// Noise algorithms only keep roughly within [-1, 1], so leave some headroom when estimating reach
+const NOISE_OVERSHOOT: f64 = 1.25;
+// Mixed into strand numbers so that their channels are hashed well away from the small numbers
+// used by the width and shimmer channels
+const STRAND_CHANNEL_SALT: u32 = 0x9e37_79b9;
+
+/// Per-octave amplitude multipliers, applied on top of the laurancity falloff
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OctaveWeights(pub [f64; MAX_OCTAVES]);
+
+impl OctaveWeights {
+	#[inline(always)]
+	pub fn get(&self, octave: usize) -> f64 {
+		self.0.get(octave).copied().unwrap_or(1.0)
+	}
+}
+
+impl Default for OctaveWeights {
+	fn default() -> Self {
+		Self([1.0; MAX_OCTAVES])
+	}
+}
+
+impl Data for OctaveWeights {
+	fn same(&self, other: &Self) -> bool {
+		self == other
+	}
+}
+
+#[derive(Data, Clone, Copy, Debug, PartialEq)]
 pub struct FractalNoise {
 	seed: u32,
 	pub laurancity: f64,
+	pub lacunarity: f64,
+	pub amplitude: f64,
 	pub octaves: i8,
+	pub weights: OctaveWeights,
+	pub algorithm: NoiseObj,
+	pub style: OctaveStyle,
 }
 
 impl FractalNoise {
@@ -17,40 +69,67 @@ impl FractalNoise {
 		Self {
 			seed,
 			laurancity,
+			lacunarity: 2.0,
+			amplitude: 3.0,
 			octaves,
+			weights: OctaveWeights::default(),
+			algorithm: NoiseObj::default(),
+			style: OctaveStyle::default(),
 		}
 	}
 
-	// Information taken from skeeto/hash-prospector
-	#[inline(always)]
-	fn get_hash(mut i: u32) -> u32 {
-		i ^= i >> 16;
-		i = i.wrapping_mul(0x21f0aaad);
-		i ^= i >> 15;
-		i = i.wrapping_mul(0xd35a2d97);
-		i ^= i >> 15;
-		i
+	pub fn seed(&self) -> u32 {
+		self.seed
 	}
 
-	#[inline(always)]
-	fn smooth_step(start: f64, end: f64, x: f64) -> f64 {
-		start + (((3.0 * x.powi(2)) - (2.0 * x.powi(3))) * (end - start))
+	/// A copy of this noise with a decorrelated seed, for driving secondary properties
+	pub fn with_channel(&self, channel: u32) -> Self {
+		Self {
+			seed: get_hash(self.seed ^ channel),
+			..*self
+		}
+	}
+
+	/// A copy of this noise for one strand of a multi-strand line, with a channel of its own that
+	/// the width and shimmer don't use
+	pub fn for_strand(&self, strand: i32) -> Self {
+		self.with_channel(get_hash(STRAND_CHANNEL_SALT ^ strand as u32))
+	}
+
+	// Frequency and amplitude multipliers of each octave. The base wavelength counts as the first
+	// octave but adds nothing, so there is one fewer term than octaves, each with its own weight.
+	fn octave_scales(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+		(1..self.octaves.max(1) as i32).map(move |exponent| {
+			(
+				self.lacunarity.powi(exponent),
+				self.laurancity.powi(exponent) * self.weights.get(exponent as usize - 1),
+			)
+		})
+	}
+
+	/// The furthest from zero the noise can get, assuming each octave lies within [-1, 1]
+	pub fn max_magnitude(&self) -> f64 {
+		self.octave_scales()
+			.map(|(_, scale)| scale.abs())
+			.sum::<f64>()
+			* self.amplitude.abs()
 	}
 
 	pub fn get(&self, distance: f64) -> f64 {
 		let mut val = 0.0;
-		for i in 1..self.octaves {
-			let dist_scaled = distance * 2.0_f64.powi(i.into()) as f64;
-			let floor = dist_scaled.floor();
-			let (ceil, fract) = ((floor + 1.0) as u32, dist_scaled - floor);
-			let floor = floor as u32;
-			val += FractalNoise::smooth_step(
-				(Self::get_hash(self.seed.wrapping_mul(floor)) % 3) as f64 - 1.0,
-				(Self::get_hash(self.seed.wrapping_mul(ceil)) % 3) as f64 - 1.0,
-				fract,
-			) * self.laurancity.powi(i.into())
+		for (frequency, scale) in self.octave_scales() {
+			val += self
+				.style
+				.apply(self.algorithm.sample(self.seed, distance * frequency))
+				* scale
 		}
-		val
+		val * self.amplitude
+	}
+}
+
+impl Default for FractalNoise {
+	fn default() -> Self {
+		Self::new(0, 0.0, 0)
 	}
 }
 
@@ -63,11 +142,125 @@ pub struct FractalLine {
 	pub wavelength: f64,
 	pub sample_distance: f64,
 	pub offset: f64,
+	pub taper: Taper,
+	pub stroke: Stroke,
+	pub animation: Animation,
 }
 
-impl FractalLinePathIter {
-	fn smooth_to_zero(x: f64) -> f64 {
-		1.0 - ((2.0 * x) - 1.0).powi(16)
+impl FractalLine {
+	/// This line as it appears at the given animation time in seconds
+	pub fn at_time(&self, time: f64) -> Self {
+		Self {
+			animation: Animation {
+				time,
+				..self.animation
+			},
+			..*self
+		}
+	}
+
+	// Displacement of the line at a distance along the noise, taking the animation into account
+	fn displacement(&self, distance: f64) -> f64 {
+		let distance = distance + self.animation.time * self.animation.crawl_speed;
+		if self.animation.shimmer_speed == 0.0 {
+			return self.noise.get(distance);
+		}
+		// Blend between successive reseedings of the noise, giving a time dimension
+		let phase = self.animation.time * self.animation.shimmer_speed;
+		let frame = phase.floor();
+		let t = phase - frame;
+		let t = (3.0 * t.powi(2)) - (2.0 * t.powi(3));
+		let from = self.noise.with_channel(frame as i64 as u32).get(distance);
+		let to = self
+			.noise
+			.with_channel((frame + 1.0) as i64 as u32)
+			.get(distance);
+		from + (to - from) * t
+	}
+
+	pub fn paint_with_color(&self, ctx: &mut druid::PaintCtx, color: &Color) {
+		match self.stroke.mode {
+			StrokeMode::Line => ctx.stroke(self, color, self.stroke.width),
+			StrokeMode::Filled => ctx.fill(self.outline(PAINT_TOLERANCE), color),
+		}
+	}
+
+	/// Freeze the line as it currently looks into a fixed path
+	pub fn to_path_shape(&self) -> PathShape {
+		match self.stroke.mode {
+			StrokeMode::Line => {
+				PathShape::stroked(self.to_path(PAINT_TOLERANCE), self.stroke.width)
+			}
+			StrokeMode::Filled => PathShape::filled(self.outline(PAINT_TOLERANCE)),
+		}
+	}
+
+	// How far the stroke reaches past the line's path
+	fn stroke_margin(&self) -> f64 {
+		match self.stroke.mode {
+			StrokeMode::Line => self.stroke.width / 2.0 * MITER_LIMIT,
+			// The width channel only differs from the noise in its seed, so shares its magnitude
+			StrokeMode::Filled => {
+				self.stroke
+					.max_width(self.noise.max_magnitude() * NOISE_OVERSHOOT)
+					/ 2.0
+			}
+		}
+	}
+
+	// The bounds of the line exactly as it is painted
+	fn path_bounds(&self) -> Rect {
+		match self.stroke.mode {
+			StrokeMode::Line => {
+				let margin = self.stroke_margin();
+				self.to_path(PAINT_TOLERANCE)
+					.bounding_box()
+					.inflate(margin, margin)
+			}
+			StrokeMode::Filled => self.outline(PAINT_TOLERANCE).bounding_box(),
+		}
+	}
+
+	// Bounds covering everywhere the line can reach as it animates, from the most it can be displaced
+	fn reach_bounds(&self) -> Rect {
+		let reach = self.width.abs() * self.noise.max_magnitude() * NOISE_OVERSHOOT;
+		let dir = self.end - self.start;
+		let side = if dir.hypot() > 0.0 {
+			Vec2::new(dir.y, -dir.x).normalize() * reach
+		} else {
+			Vec2::new(reach, reach)
+		};
+		let margin = self.stroke_margin();
+		Rect::from_points(self.start + side, self.start - side)
+			.union(Rect::from_points(self.end + side, self.end - side))
+			.inflate(margin, margin)
+	}
+
+	/// A closed outline around the line, widened according to its stroke
+	pub fn outline(&self, tolerance: f64) -> BezPath {
+		let width_noise = self.noise.with_channel(1);
+		let mut iter = self.path_elements(tolerance);
+		let perpendicular = iter.perpendicular;
+		let mut left = Vec::new();
+		let mut right = Vec::new();
+		while let Some((index, point)) = iter.next_sample() {
+			let half_width = self
+				.stroke
+				.half_width(index, width_noise.get(iter.noise_distance(index)));
+			left.push(point + perpendicular * half_width);
+			right.push(point - perpendicular * half_width);
+		}
+
+		let mut path = BezPath::new();
+		for (i, p) in left.into_iter().chain(right.into_iter().rev()).enumerate() {
+			if i == 0 {
+				path.move_to(p);
+			} else {
+				path.line_to(p);
+			}
+		}
+		path.close_path();
+		path
 	}
 }
 
@@ -94,32 +287,54 @@ impl FractalLinePathIter {
 	}
 }
 
-impl Iterator for FractalLinePathIter {
-	type Item = PathEl;
+impl FractalLinePathIter {
+	// The distance along the noise that a position in [0, 1] along the line samples
+	fn noise_distance(&self, index: f64) -> f64 {
+		(self.real_length * index / self.line_data.wavelength) + self.line_data.offset
+	}
 
-	fn next(&mut self) -> Option<Self::Item> {
+	// Produces the next point on the line alongside its position in [0, 1]
+	fn next_sample(&mut self) -> Option<(f64, Point)> {
 		if self.i > self.segments as i32 {
 			return None;
 		}
 
 		if self.i == self.segments as i32 {
 			self.i += 1;
-			return Some(druid::piet::kurbo::PathEl::LineTo(self.line_data.end));
+			return Some((1.0, self.line_data.end));
 		}
 		let index = self.i as f64 / self.segments as f64;
 		self.i += 1;
 
-		let simplex_distance =
-			(self.real_length * index / self.line_data.wavelength) + self.line_data.offset;
-		let simplex = self.line_data.noise.get(simplex_distance) * 3.0;
+		let simplex = self.line_data.displacement(self.noise_distance(index));
 
-		Some(druid::piet::kurbo::PathEl::LineTo(
+		Some((
+			index,
 			self.line_data.start.lerp(self.line_data.end, index)
-				+ self.perpendicular * self.line_data.width * Self::smooth_to_zero(index) * simplex,
+				+ self.perpendicular
+					* self.line_data.width
+					* self.line_data.taper.envelope(index)
+					* simplex,
 		))
 	}
 }
 
+impl Iterator for FractalLinePathIter {
+	type Item = PathEl;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let first = self.i == 0;
+		self.next_sample().map(|(_, point)| {
+			// Start a new subpath so that joined paths don't connect to each other
+			if first {
+				druid::piet::kurbo::PathEl::MoveTo(point)
+			} else {
+				druid::piet::kurbo::PathEl::LineTo(point)
+			}
+		})
+	}
+}
+
 impl Shape for FractalLine {
 	type PathElementsIter = FractalLinePathIter;
 
@@ -144,39 +359,109 @@ impl Shape for FractalLine {
 	}
 
 	fn bounding_box(&self) -> Rect {
-		Rect::from_points(self.start, self.end).inflate(self.width * 10.5, self.width * 10.5)
+		// An animated line moves over time, so its bounds have to hold wherever it goes
+		if self.animation.is_animated() {
+			self.reach_bounds()
+		} else {
+			self.path_bounds()
+		}
 	}
 }
 
 impl Drawable for FractalLine {
+	// Found from how far the noise can reach rather than by building the path, as this is needed
+	// for every repaint
 	fn AABB(&self) -> Rect {
+		self.reach_bounds()
+	}
+
+	fn exact_bounds(&self) -> Rect {
 		self.bounding_box()
 	}
 
 	fn fine_collision_shape(&self, tolerance: f64) -> BezPath {
-		self.to_path(tolerance)
+		match self.stroke.mode {
+			StrokeMode::Line => self.to_path(tolerance),
+			StrokeMode::Filled => self.outline(tolerance),
+		}
 	}
 
-	#[allow(unused_variables)]
-	fn event(
-		&mut self,
-		ctx: &mut druid::EventCtx,
-		event: &druid::Event,
-		env: &druid::Env,
-		sctx: &RenderObject,
-	) {
-		todo!()
+	fn is_animated(&self) -> bool {
+		self.animation.is_animated()
 	}
 
-	fn paint(&self, ctx: &mut druid::PaintCtx, _env: &druid::Env, sctx: &RenderObject) {
-		ctx.stroke(
-			self,
-			if sctx.is_selected() {
-				&Color::RED
-			} else {
-				&Color::BLACK
-			},
-			1.0,
-		);
+	fn snap_points(&self) -> Vec<SnapTarget> {
+		vec![
+			SnapTarget::new(self.start, SnapKind::Endpoint),
+			SnapTarget::new(self.end, SnapKind::Endpoint),
+			SnapTarget::new(self.start.midpoint(self.end), SnapKind::Midpoint),
+		]
+	}
+
+	fn handles(&self) -> Vec<Point> {
+		vec![self.start, self.end]
+	}
+
+	// The seed and offset are kept, so the line keeps its character as it is reshaped
+	fn move_handle(&mut self, index: usize, to: Point) {
+		match index {
+			0 => self.start = to,
+			1 => self.end = to,
+			_ => (),
+		}
+	}
+
+	fn paint(&self, ctx: &mut druid::PaintCtx, env: &druid::Env, sctx: &RenderObject) {
+		self.at_time(env.try_get(ANIMATION_TIME).unwrap_or(0.0))
+			.paint_with_color(
+				ctx,
+				&if sctx.is_selected() {
+					env.get(SELECTION_COLOR)
+				} else {
+					Color::BLACK
+				},
+			);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// FractalNoise::get before lacunarity, weights and amplitude were configurable
+	fn original_get(seed: u32, laurancity: f64, octaves: i8, distance: f64) -> f64 {
+		let smooth_step = |start: f64, end: f64, x: f64| {
+			start + (((3.0 * x.powi(2)) - (2.0 * x.powi(3))) * (end - start))
+		};
+		let mut val = 0.0;
+		for i in 1..octaves {
+			let dist_scaled = distance * 2.0_f64.powi(i.into());
+			let floor = dist_scaled.floor();
+			let (ceil, fract) = ((floor + 1.0) as u32, dist_scaled - floor);
+			let floor = floor as u32;
+			val += smooth_step(
+				(get_hash(seed.wrapping_mul(floor)) % 3) as f64 - 1.0,
+				(get_hash(seed.wrapping_mul(ceil)) % 3) as f64 - 1.0,
+				fract,
+			) * laurancity.powi(i.into())
+		}
+		val
+	}
+
+	#[test]
+	fn defaults_match_the_original_noise() {
+		for octaves in 0..=4 {
+			let noise = FractalNoise::new(1234, 0.4, octaves);
+			for i in 0..200 {
+				let distance = i as f64 * 0.137;
+				let expected = original_get(1234, 0.4, octaves, distance) * 3.0;
+				assert!(
+					(noise.get(distance) - expected).abs() < 1e-12,
+					"{} octaves differ at {}",
+					octaves,
+					distance
+				);
+			}
+		}
 	}
 }
@@ -1,9 +1,20 @@
+pub mod animation;
+pub mod curve;
 pub mod drawable;
+#[cfg(test)]
+pub mod fixtures;
 pub mod fractal_line;
+pub mod multi_strand_line;
+pub mod path_shape;
 pub mod render_object;
-pub use render_object::RenderObject;
+pub use render_object::{ObjectId, RenderObject};
 pub mod selection_rect;
+pub mod snap_target;
+pub mod stroke;
+pub mod taper;
 
 pub use drawable::DrawableObj as Drawable;
 pub use fractal_line::FractalLine;
+pub use multi_strand_line::MultiStrandLine;
+pub use path_shape::PathShape;
 pub use selection_rect::SelectionRect;
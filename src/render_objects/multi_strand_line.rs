@@ -0,0 +1,147 @@
+use druid::{kurbo::BezPath, Color, Data, Lens, Point, Rect, Vec2};
+
+use crate::preferences::SELECTION_COLOR;
+
+use super::{
+	animation::ANIMATION_TIME, drawable::Drawable, snap_target::SnapTarget, FractalLine,
+	RenderObject,
+};
+
+/// How a MultiStrandLine repeats its line
+#[derive(Data, Clone, Copy, Debug, PartialEq, Lens)]
+pub struct Strands {
+	pub count: i32,
+	// Perpendicular distance between neighbouring strands
+	pub spacing: f64,
+	// Added to the noise offset of each successive strand
+	pub offset_step: f64,
+	// Give every strand its own noise seed rather than sharing the line's
+	pub vary_seed: bool,
+	// How much opacity the outermost strands lose, from 0 (none) to 1 (invisible)
+	pub fade: f64,
+}
+
+impl Default for Strands {
+	fn default() -> Self {
+		Self {
+			count: 1,
+			spacing: 10.0,
+			offset_step: 0.0,
+			vary_seed: false,
+			fade: 0.5,
+		}
+	}
+}
+
+/// Several parallel copies of a fractal line, selected and edited as one object
+#[derive(Data, Clone, Copy, Debug, PartialEq)]
+pub struct MultiStrandLine {
+	pub line: FractalLine,
+	pub strands: Strands,
+}
+
+impl MultiStrandLine {
+	pub fn new(line: FractalLine, strands: Strands) -> Self {
+		Self { line, strands }
+	}
+
+	// Position of a strand relative to the center strand, in units of spacing
+	fn strand_position(&self, i: i32) -> f64 {
+		i as f64 - (self.strands.count - 1) as f64 / 2.0
+	}
+
+	pub fn strand(&self, i: i32) -> FractalLine {
+		let dir = self.line.end - self.line.start;
+		let perpendicular = if dir.hypot() == 0.0 {
+			Vec2::ZERO
+		} else {
+			Vec2::new(dir.y, -dir.x).normalize()
+		};
+		let shift = perpendicular * self.strand_position(i) * self.strands.spacing;
+		FractalLine {
+			start: self.line.start + shift,
+			end: self.line.end + shift,
+			noise: if self.strands.vary_seed {
+				self.line.noise.for_strand(i)
+			} else {
+				self.line.noise
+			},
+			offset: self.line.offset + i as f64 * self.strands.offset_step,
+			..self.line
+		}
+	}
+
+	pub fn strand_opacity(&self, i: i32) -> f64 {
+		let half = (self.strands.count - 1) as f64 / 2.0;
+		if half <= 0.0 {
+			return 1.0;
+		}
+		1.0 - self.strands.fade * (self.strand_position(i).abs() / half)
+	}
+
+	pub fn strand_lines(&self) -> impl Iterator<Item = FractalLine> + '_ {
+		(0..self.strands.count.max(1)).map(move |i| self.strand(i))
+	}
+}
+
+impl Drawable for MultiStrandLine {
+	fn AABB(&self) -> Rect {
+		self.strand_lines()
+			.map(|line| line.AABB())
+			.reduce(|acc, rect| acc.union(rect))
+			.unwrap_or_default()
+	}
+
+	fn exact_bounds(&self) -> Rect {
+		self.strand_lines()
+			.map(|line| line.exact_bounds())
+			.reduce(|acc, rect| acc.union(rect))
+			.unwrap_or_default()
+	}
+
+	fn fine_collision_shape(&self, tolerance: f64) -> BezPath {
+		let mut path = BezPath::new();
+		for line in self.strand_lines() {
+			for el in line.fine_collision_shape(tolerance).elements() {
+				path.push(*el);
+			}
+		}
+		path
+	}
+
+	fn is_animated(&self) -> bool {
+		self.line.is_animated()
+	}
+
+	fn snap_points(&self) -> Vec<SnapTarget> {
+		self.strand_lines()
+			.flat_map(|line| line.snap_points())
+			.collect()
+	}
+
+	fn handles(&self) -> Vec<Point> {
+		self.line.handles()
+	}
+
+	fn move_handle(&mut self, index: usize, to: Point) {
+		self.line.move_handle(index, to)
+	}
+
+	fn paint(&self, ctx: &mut druid::PaintCtx, env: &druid::Env, sctx: &RenderObject) {
+		let animated = Self::new(
+			self.line
+				.at_time(env.try_get(ANIMATION_TIME).unwrap_or(0.0)),
+			self.strands,
+		);
+		let color = if sctx.is_selected() {
+			env.get(SELECTION_COLOR)
+		} else {
+			Color::BLACK
+		};
+		for i in 0..self.strands.count.max(1) {
+			animated
+				.strand(i)
+				.paint_with_color(ctx, &color.clone().with_alpha(self.strand_opacity(i)));
+		}
+	}
+}
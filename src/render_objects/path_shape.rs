@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use druid::{
+	kurbo::{BezPath, PathEl, Shape},
+	Color, Data, Point, Rect, RenderContext,
+};
+
+use super::{
+	drawable::Drawable,
+	snap_target::{SnapKind, SnapTarget},
+	RenderObject,
+};
+use crate::preferences::SELECTION_COLOR;
+
+// Mitred corners of a stroke can reach this many half widths from the path, piet's default limit
+const MITER_LIMIT: f64 = 10.0;
+
+/// A fixed path, either stroked or filled, such as a fractal line that has been converted to a path
+#[derive(Data, Clone)]
+pub struct PathShape {
+	pub path: Arc<BezPath>,
+	// Filled shapes are closed outlines, others are stroked along the path
+	pub filled: bool,
+	pub width: f64,
+	pub opacity: f64,
+}
+
+impl PathShape {
+	pub fn stroked(path: BezPath, width: f64) -> Self {
+		Self {
+			path: Arc::new(path),
+			filled: false,
+			width,
+			opacity: 1.0,
+		}
+	}
+
+	pub fn filled(path: BezPath) -> Self {
+		Self {
+			path: Arc::new(path),
+			filled: true,
+			width: 0.0,
+			opacity: 1.0,
+		}
+	}
+
+	pub fn with_opacity(self, opacity: f64) -> Self {
+		Self { opacity, ..self }
+	}
+
+	// Indices into the path's elements of every element that ends on a point
+	fn vertex_elements(&self) -> impl Iterator<Item = (usize, Point)> + '_ {
+		self.path
+			.elements()
+			.iter()
+			.enumerate()
+			.filter_map(|(i, el)| match *el {
+				PathEl::MoveTo(p) | PathEl::LineTo(p) => Some((i, p)),
+				PathEl::QuadTo(_, p) | PathEl::CurveTo(_, _, p) => Some((i, p)),
+				PathEl::ClosePath => None,
+			})
+	}
+}
+
+impl Drawable for PathShape {
+	fn AABB(&self) -> Rect {
+		let margin = if self.filled {
+			0.0
+		} else {
+			self.width / 2.0 * MITER_LIMIT
+		};
+		self.path.bounding_box().inflate(margin, margin)
+	}
+
+	// Curves are flattened, so that collision code only ever has to deal with lines
+	fn fine_collision_shape(&self, tolerance: f64) -> BezPath {
+		let mut lines = BezPath::new();
+		self.path.flatten(tolerance, |el| lines.push(el));
+		lines
+	}
+
+	fn snap_points(&self) -> Vec<SnapTarget> {
+		if self.filled {
+			return Vec::new();
+		}
+		let vertices: Vec<Point> = self.vertex_elements().map(|(_, p)| p).collect();
+		vertices
+			.first()
+			.into_iter()
+			.chain(vertices.last())
+			.map(|p| SnapTarget::new(*p, SnapKind::Endpoint))
+			.collect()
+	}
+
+	fn handles(&self) -> Vec<Point> {
+		self.vertex_elements().map(|(_, p)| p).collect()
+	}
+
+	// Control points of curves either side of a vertex move with it, keeping the curve's shape
+	fn move_handle(&mut self, index: usize, to: Point) {
+		let (element, from) = match self.vertex_elements().nth(index) {
+			Some(vertex) => vertex,
+			None => return,
+		};
+		let offset = to - from;
+		let mut elements = self.path.elements().to_vec();
+		match &mut elements[element] {
+			PathEl::MoveTo(p) | PathEl::LineTo(p) => *p = to,
+			PathEl::QuadTo(_, p) => *p = to,
+			PathEl::CurveTo(_, c2, p) => {
+				*c2 += offset;
+				*p = to;
+			}
+			PathEl::ClosePath => (),
+		}
+		if let Some(PathEl::CurveTo(c1, _, _)) = elements.get_mut(element + 1) {
+			*c1 += offset;
+		}
+		self.path = Arc::new(BezPath::from_vec(elements));
+	}
+
+	fn paint(&self, ctx: &mut druid::PaintCtx, env: &druid::Env, sctx: &RenderObject) {
+		let color = if sctx.is_selected() {
+			env.get(SELECTION_COLOR)
+		} else {
+			Color::BLACK
+		}
+		.with_alpha(self.opacity);
+		if self.filled {
+			ctx.fill(&*self.path, &color);
+		} else {
+			ctx.stroke(&*self.path, &color, self.width);
+		}
+	}
+}
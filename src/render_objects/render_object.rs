@@ -1,9 +1,28 @@
 use super::drawable::DrawableObj;
-use druid::{Affine, Color, Data, RenderContext};
-use std::fmt::Debug;
+use druid::{
+	kurbo::{BezPath, ParamCurveNearest, PathEl, Shape},
+	Affine, Color, Data, Point, Rect, RenderContext,
+};
+use std::{
+	fmt::Debug,
+	sync::atomic::{AtomicU64, Ordering},
+};
 
-#[derive(Data, Clone, Copy)]
+/// Identifies an object for as long as it exists, wherever it moves to in the scene
+#[derive(Data, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectId(u64);
+
+impl ObjectId {
+	/// An id that no other object has been given since the program started
+	pub fn next() -> Self {
+		static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+		Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+	}
+}
+
+#[derive(Data, Clone)]
 pub struct RenderObject {
+	pub id: ObjectId,
 	pub transform: Affine,
 	pub selected: bool,
 	pub drawable: DrawableObj,
@@ -12,8 +31,9 @@ pub struct RenderObject {
 impl Debug for RenderObject {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		f.debug_struct("RenderObject")
+			.field("id", &self.id)
 			.field("transform", &self.transform)
-			.field("drawable", &self.drawable.AABB())
+			.field("drawable", &self.bounds())
 			.finish()
 	}
 }
@@ -28,6 +48,7 @@ impl RenderObject {
 
 	pub fn new(drawable: DrawableObj) -> Self {
 		Self {
+			id: ObjectId::next(),
 			transform: Affine::new([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]),
 			drawable,
 			selected: false,
@@ -38,10 +59,40 @@ impl RenderObject {
 		&self.drawable
 	}
 
+	/// The bounds of the object in canvas space, taking its transform into account
+	pub fn bounds(&self) -> Rect {
+		self.transform.transform_rect_bbox(self.drawable.AABB())
+	}
+
+	/// The tightest bounds of the object in canvas space, slower to work out than `bounds`
+	pub fn exact_bounds(&self) -> Rect {
+		self.transform
+			.transform_rect_bbox(self.drawable.exact_bounds())
+	}
+
+	/// The bounds of the object after transforming it from canvas space into another space.
+	///
+	/// Tighter than transforming `bounds`, as the object is only boxed once.
+	pub fn bounds_in(&self, space: Affine) -> Rect {
+		(space * self.transform).transform_rect_bbox(self.drawable.AABB())
+	}
+
+	/// The object's collision shape in canvas space as it looks at an animation time, flattened to
+	/// within tolerance canvas units
+	pub fn collision_shape(&self, tolerance: f64, time: f64) -> BezPath {
+		// A scaled up object has to be flattened more finely to stay within tolerance once transformed
+		let scale = self.transform.determinant().abs().sqrt().max(f64::EPSILON);
+		let mut shape = self
+			.drawable
+			.at_time(time)
+			.fine_collision_shape(tolerance / scale);
+		shape.apply_affine(self.transform);
+		shape
+	}
+
 	#[allow(dead_code)] // Exists for possible debug use
 	pub fn paint_bounds(&self, ctx: &mut druid::PaintCtx, _env: &druid::Env) {
-		ctx.transform(self.transform);
-		ctx.with_save(|new_ctx| new_ctx.stroke(self.drawable.AABB(), &Color::RED, 1.0))
+		ctx.stroke(self.bounds(), &Color::RED, 1.0)
 	}
 
 	pub fn select(&mut self) {
@@ -55,4 +106,100 @@ impl RenderObject {
 	pub fn is_selected(&self) -> bool {
 		self.selected
 	}
+
+	/// Whether a canvas space point lies inside the object or within radius of its outline, as
+	/// the object looks at an animation time. The outline is flattened to the given tolerance
+	pub fn hit_test(&self, point: Point, radius: f64, tolerance: f64, time: f64) -> bool {
+		if !self.bounds().inflate(radius, radius).contains(point) {
+			return false;
+		}
+		// Tested in canvas space, so the radius is the same however the object is scaled
+		let shape = self.collision_shape(tolerance, time);
+		closed_subpaths(&shape).winding(point) != 0
+			|| shape
+				.segments()
+				.any(|segment| segment.nearest(point, 0.1).1 <= radius.powi(2))
+	}
+}
+
+// The subpaths of a path that end by closing, as open lines don't enclose anything a point could
+// be inside of, even where they curl back on themselves
+fn closed_subpaths(path: &BezPath) -> BezPath {
+	let mut closed = BezPath::new();
+	let mut subpath: Vec<PathEl> = Vec::new();
+	for el in path.elements() {
+		match *el {
+			PathEl::MoveTo(_) => subpath = vec![*el],
+			PathEl::ClosePath => {
+				for el in subpath.drain(..) {
+					closed.push(el);
+				}
+				closed.close_path();
+			}
+			el => subpath.push(el),
+		}
+	}
+	closed
+}
+
+#[cfg(test)]
+mod tests {
+	use std::f64::consts::FRAC_PI_4;
+
+	use super::*;
+	use crate::render_objects::fixtures::square;
+
+	fn assert_rect_near(a: Rect, b: Rect) {
+		let close = (a.x0 - b.x0).abs() < 1e-9
+			&& (a.y0 - b.y0).abs() < 1e-9
+			&& (a.x1 - b.x1).abs() < 1e-9
+			&& (a.y1 - b.y1).abs() < 1e-9;
+		assert!(close, "{:?} is not {:?}", a, b);
+	}
+
+	#[test]
+	fn scaled_bounds() {
+		let object = square(Affine::scale(2.0));
+		assert_rect_near(object.bounds(), Rect::new(0.0, 0.0, 20.0, 20.0));
+	}
+
+	#[test]
+	fn rotated_bounds() {
+		// Turned a quarter about its corner the square becomes a diamond standing on that corner
+		let object = square(Affine::rotate(FRAC_PI_4));
+		let half_diagonal = 50.0f64.sqrt();
+		assert_rect_near(
+			object.bounds(),
+			Rect::new(-half_diagonal, 0.0, half_diagonal, 2.0 * half_diagonal),
+		);
+	}
+
+	#[test]
+	fn hit_test_scaled() {
+		let object = square(Affine::scale(2.0));
+		assert!(object.hit_test(Point::new(15.0, 15.0), 0.5, 0.5, 0.0));
+		assert!(object.hit_test(Point::new(20.3, 5.0), 0.5, 0.5, 0.0));
+		assert!(!object.hit_test(Point::new(25.0, 5.0), 0.5, 0.5, 0.0));
+	}
+
+	#[test]
+	fn hit_test_rotated() {
+		let object = square(Affine::rotate(FRAC_PI_4));
+		// The top of the diamond, outside where the square was before turning
+		assert!(object.hit_test(Point::new(0.0, 13.0), 0.5, 0.5, 0.0));
+		// Inside the square before turning, but off to the side of the diamond
+		assert!(!object.hit_test(Point::new(8.0, 2.0), 0.5, 0.5, 0.0));
+	}
+
+	#[test]
+	fn open_path_only_hits_near_its_line() {
+		let mut path = BezPath::new();
+		path.move_to((0.0, 0.0));
+		path.line_to((10.0, 0.0));
+		path.line_to((10.0, 10.0));
+		let object = RenderObject::new(Drawable::PathShape(PathShape::stroked(path, 1.0)));
+		assert!(object.hit_test(Point::new(5.0, 0.4), 0.5, 0.5, 0.0));
+		// Inside the corner the line turns, which would count if the path were closed
+		assert!(!object.hit_test(Point::new(7.0, 3.0), 0.5, 0.5, 0.0));
+	}
 }
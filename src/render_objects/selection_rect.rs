@@ -5,6 +5,7 @@ use druid::{
 };
 
 use super::drawable::Drawable;
+use crate::preferences::{SELECTION_DASH, SELECTION_GAP};
 
 const SELECTION_BRUSH: PaintBrush = PaintBrush::Color(Color::BLACK);
 
@@ -28,22 +29,12 @@ impl Drawable for SelectionRect {
 		self.AABB().to_path(tolerance)
 	}
 
-	fn event(
-		&mut self,
-		_ctx: &mut druid::EventCtx,
-		_event: &druid::Event,
-		_env: &druid::Env,
-		_sctx: &super::RenderObject,
-	) {
-		todo!()
-	}
-
-	fn paint(&self, ctx: &mut druid::PaintCtx, _env: &druid::Env, _sctx: &super::RenderObject) {
+	fn paint(&self, ctx: &mut druid::PaintCtx, env: &druid::Env, _sctx: &super::RenderObject) {
 		ctx.stroke_styled(
 			self.rect,
 			&SELECTION_BRUSH,
 			1.0,
-			&StrokeStyle::new().dash(vec![3.0, 3.0], 0.0),
+			&StrokeStyle::new().dash(vec![env.get(SELECTION_DASH), env.get(SELECTION_GAP)], 0.0),
 		);
 	}
 }
@@ -0,0 +1,75 @@
+use druid::{
+	kurbo::{Circle, Line},
+	Affine, Color, Data, Point, Rect, RenderContext, Size,
+};
+
+#[derive(Data, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapKind {
+	Grid,
+	Endpoint,
+	Midpoint,
+	Intersection,
+	Angle,
+}
+
+/// A point that the pointer has been snapped to
+#[derive(Data, Clone, Copy, Debug, PartialEq)]
+pub struct SnapTarget {
+	pub point: Point,
+	pub kind: SnapKind,
+}
+
+// Size in screen pixels of the indicator drawn over the active snap target
+const INDICATOR_SIZE: f64 = 10.0;
+
+impl SnapTarget {
+	pub fn new(point: Point, kind: SnapKind) -> Self {
+		Self { point, kind }
+	}
+
+	/// The area in widget space covered by the indicator for this target
+	pub fn indicator_rect(&self, to_widget: Affine) -> Rect {
+		Rect::from_center_size(
+			to_widget * self.point,
+			Size::new(INDICATOR_SIZE, INDICATOR_SIZE),
+		)
+		.inflate(2.0, 2.0)
+	}
+
+	/// Paint a marker over the target, ctx is expected to be in widget space
+	pub fn paint_indicator(&self, ctx: &mut druid::PaintCtx, to_widget: Affine) {
+		let center = to_widget * self.point;
+		let rect = Rect::from_center_size(center, Size::new(INDICATOR_SIZE, INDICATOR_SIZE));
+		let color = Color::rgb8(0, 120, 215);
+		match self.kind {
+			SnapKind::Grid => {
+				ctx.stroke(
+					Line::new((rect.x0, center.y), (rect.x1, center.y)),
+					&color,
+					1.0,
+				);
+				ctx.stroke(
+					Line::new((center.x, rect.y0), (center.x, rect.y1)),
+					&color,
+					1.0,
+				);
+			}
+			SnapKind::Endpoint => ctx.stroke(rect, &color, 1.5),
+			SnapKind::Midpoint | SnapKind::Angle => {
+				ctx.stroke(Circle::new(center, INDICATOR_SIZE / 2.0), &color, 1.5)
+			}
+			SnapKind::Intersection => {
+				ctx.stroke(
+					Line::new((rect.x0, rect.y0), (rect.x1, rect.y1)),
+					&color,
+					1.5,
+				);
+				ctx.stroke(
+					Line::new((rect.x0, rect.y1), (rect.x1, rect.y0)),
+					&color,
+					1.5,
+				);
+			}
+		}
+	}
+}
@@ -0,0 +1,97 @@
+use druid::{Data, Lens};
+
+use super::{
+	curve::Curve,
+	taper::{Taper, TaperKind},
+};
+
+#[derive(Data, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrokeMode {
+	// A constant width polyline
+	Line,
+	// A filled outline whose width can vary along the line
+	Filled,
+}
+
+impl StrokeMode {
+	pub fn variants() -> Vec<(&'static str, StrokeMode)> {
+		vec![("Line", StrokeMode::Line), ("Filled", StrokeMode::Filled)]
+	}
+}
+
+#[derive(Data, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WidthSource {
+	Constant,
+	// A second noise channel, decorrelated from the line's displacement
+	Noise,
+	// Follows the pressure taper, like a brush pressed down and lifted
+	Pressure,
+	Curve,
+}
+
+impl WidthSource {
+	pub fn variants() -> Vec<(&'static str, WidthSource)> {
+		vec![
+			("Constant", WidthSource::Constant),
+			("Noise", WidthSource::Noise),
+			("Pressure", WidthSource::Pressure),
+			("Curve", WidthSource::Curve),
+		]
+	}
+}
+
+#[derive(Data, Clone, Copy, Debug, PartialEq, Lens)]
+pub struct Stroke {
+	pub mode: StrokeMode,
+	pub width: f64,
+	pub source: WidthSource,
+	// How strongly the noise channel affects the width
+	pub variation: f64,
+	pub pressure: Taper,
+	pub curve: Curve,
+}
+
+impl Stroke {
+	/// Half of the stroke width at a position in [0, 1] along the line.
+	///
+	/// `noise` is the line's secondary noise channel sampled at that position.
+	pub fn half_width(&self, x: f64, noise: f64) -> f64 {
+		let factor = match self.source {
+			WidthSource::Constant => 1.0,
+			WidthSource::Noise => (1.0 + self.variation * noise).max(0.0),
+			WidthSource::Pressure => self.pressure.envelope(x),
+			WidthSource::Curve => self.curve.sample(x),
+		};
+		self.width * factor / 2.0
+	}
+
+	/// An upper bound on the stroke width given the furthest the noise channel gets from zero
+	pub fn max_width(&self, noise_magnitude: f64) -> f64 {
+		match (self.mode, self.source) {
+			(StrokeMode::Line, _) => self.width,
+			(StrokeMode::Filled, WidthSource::Noise) => {
+				self.width * (1.0 + self.variation.abs() * noise_magnitude)
+			}
+			(StrokeMode::Filled, WidthSource::Curve) => {
+				self.width * self.curve.0.iter().cloned().fold(0.0, f64::max)
+			}
+			(StrokeMode::Filled, _) => self.width,
+		}
+	}
+}
+
+impl Default for Stroke {
+	fn default() -> Self {
+		Self {
+			mode: StrokeMode::Line,
+			width: 1.0,
+			source: WidthSource::Constant,
+			variation: 0.5,
+			pressure: Taper {
+				kind: TaperKind::Cosine,
+				..Default::default()
+			},
+			curve: Curve::default(),
+		}
+	}
+}
@@ -0,0 +1,83 @@
+use std::f64::consts::PI;
+
+use druid::{Data, Lens};
+
+use super::curve::Curve;
+
+#[derive(Data, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaperKind {
+	None,
+	Linear,
+	Cosine,
+	Power,
+	Asymmetric,
+	Curve,
+}
+
+impl TaperKind {
+	pub fn variants() -> Vec<(&'static str, TaperKind)> {
+		vec![
+			("None", TaperKind::None),
+			("Linear", TaperKind::Linear),
+			("Cosine", TaperKind::Cosine),
+			("Power", TaperKind::Power),
+			("Asymmetric", TaperKind::Asymmetric),
+			("Curve", TaperKind::Curve),
+		]
+	}
+}
+
+/// The envelope that scales a line's displacement along its length.
+///
+/// Every profile maps a position in [0, 1] to a multiplier in [0, 1],
+/// a value of zero pins the line to its straight path at that point.
+#[derive(Data, Clone, Copy, Debug, PartialEq, Lens)]
+pub struct Taper {
+	pub kind: TaperKind,
+	// Used by TaperKind::Power, higher values keep the line rough closer to its ends
+	pub exponent: f64,
+	// Used by TaperKind::Asymmetric, the fraction of the line spent tapering at each end
+	pub start: f64,
+	pub end: f64,
+	pub curve: Curve,
+}
+
+impl Taper {
+	#[inline(always)]
+	fn smooth_step(x: f64) -> f64 {
+		(3.0 * x.powi(2)) - (2.0 * x.powi(3))
+	}
+
+	// Ramp from 0 to 1 over the given length, or no ramp if the length is zero
+	#[inline(always)]
+	fn ramp(x: f64, length: f64) -> f64 {
+		if length <= 0.0 {
+			1.0
+		} else {
+			Self::smooth_step((x / length).min(1.0))
+		}
+	}
+
+	pub fn envelope(&self, x: f64) -> f64 {
+		match self.kind {
+			TaperKind::None => 1.0,
+			TaperKind::Linear => 1.0 - ((2.0 * x) - 1.0).abs(),
+			TaperKind::Cosine => (x * PI).sin(),
+			TaperKind::Power => 1.0 - ((2.0 * x) - 1.0).abs().powf(self.exponent),
+			TaperKind::Asymmetric => Self::ramp(x, self.start) * Self::ramp(1.0 - x, self.end),
+			TaperKind::Curve => self.curve.sample(x),
+		}
+	}
+}
+
+impl Default for Taper {
+	fn default() -> Self {
+		Self {
+			kind: TaperKind::Power,
+			exponent: 16.0,
+			start: 0.25,
+			end: 0.25,
+			curve: Curve::default(),
+		}
+	}
+}
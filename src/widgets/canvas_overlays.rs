@@ -0,0 +1,161 @@
+use druid::{
+	kurbo::Line,
+	piet::{FontFamily, Text, TextAttribute, TextLayoutBuilder},
+	Affine, Color, Data, Lens, Point, Rect, RenderContext,
+};
+
+// Grid lines are never drawn closer together than this many screen pixels
+const MIN_GRID_SPACING: f64 = 8.0;
+// Every nth grid line is drawn as a major line, and labelled on the rulers
+const MAJOR_EVERY: f64 = 4.0;
+const RULER_SIZE: f64 = 18.0;
+const ORIGIN_SIZE: f64 = 12.0;
+
+/// Which navigational aids are drawn over the canvas
+#[derive(Data, Clone, Copy, Debug, PartialEq, Lens)]
+pub struct CanvasOverlays {
+	pub grid: bool,
+	pub rulers: bool,
+	pub origin: bool,
+}
+
+impl Default for CanvasOverlays {
+	fn default() -> Self {
+		Self {
+			grid: true,
+			rulers: true,
+			origin: true,
+		}
+	}
+}
+
+// The scale factor of a transform, ignoring any rotation
+fn scale_of(to_widget: Affine) -> f64 {
+	to_widget.determinant().abs().sqrt()
+}
+
+/// The spacing in canvas units of the finest grid lines that are visible
+///
+/// The base spacing is doubled or halved until lines are a comfortable
+/// distance apart on screen, so the grid subdivides as the view zooms in.
+pub fn adaptive_spacing(base: f64, to_widget: Affine) -> f64 {
+	let scale = scale_of(to_widget);
+	if base <= 0.0 || scale <= 0.0 {
+		return 0.0;
+	}
+	let mut spacing = base;
+	while spacing * scale < MIN_GRID_SPACING {
+		spacing *= 2.0;
+	}
+	while spacing * scale >= MIN_GRID_SPACING * 2.0 {
+		spacing /= 2.0;
+	}
+	spacing
+}
+
+/// Paint the grid in canvas space, ctx is expected to already be transformed to canvas space
+pub fn paint_grid(ctx: &mut druid::PaintCtx, port: Rect, to_widget: Affine, base: f64) {
+	let spacing = adaptive_spacing(base, to_widget);
+	if spacing <= 0.0 {
+		return;
+	}
+	let hairline = 1.0 / scale_of(to_widget);
+	let visible = to_widget.inverse().transform_rect_bbox(port);
+	let minor = Color::grey8(235);
+	let major = Color::grey8(210);
+
+	let mut x = (visible.x0 / spacing).floor() * spacing;
+	while x <= visible.x1 {
+		let color = if (x / spacing).round() % MAJOR_EVERY == 0.0 {
+			&major
+		} else {
+			&minor
+		};
+		ctx.stroke(Line::new((x, visible.y0), (x, visible.y1)), color, hairline);
+		x += spacing;
+	}
+	let mut y = (visible.y0 / spacing).floor() * spacing;
+	while y <= visible.y1 {
+		let color = if (y / spacing).round() % MAJOR_EVERY == 0.0 {
+			&major
+		} else {
+			&minor
+		};
+		ctx.stroke(Line::new((visible.x0, y), (visible.x1, y)), color, hairline);
+		y += spacing;
+	}
+}
+
+/// Paint a marker at the canvas origin showing the direction of each axis, in widget space
+pub fn paint_origin(ctx: &mut druid::PaintCtx, to_widget: Affine) {
+	let origin = to_widget * Point::ORIGIN;
+	let x_axis = (to_widget * Point::new(1.0, 0.0) - origin).normalize() * ORIGIN_SIZE;
+	let y_axis = (to_widget * Point::new(0.0, 1.0) - origin).normalize() * ORIGIN_SIZE;
+	ctx.stroke(
+		Line::new(origin, origin + x_axis),
+		&Color::rgb8(200, 40, 40),
+		1.5,
+	);
+	ctx.stroke(
+		Line::new(origin, origin + y_axis),
+		&Color::rgb8(40, 160, 40),
+		1.5,
+	);
+}
+
+fn paint_label(ctx: &mut druid::PaintCtx, text: String, pos: Point) {
+	let layout = ctx
+		.text()
+		.new_text_layout(text)
+		.font(FontFamily::SYSTEM_UI, 9.0)
+		.default_attribute(TextAttribute::TextColor(Color::grey8(80)))
+		.build();
+	if let Ok(layout) = layout {
+		ctx.draw_text(&layout, pos);
+	}
+}
+
+/// Paint rulers along the top and left of the canvas, in widget space
+///
+/// Rulers measure canvas units along the screen axes, starting from the origin.
+pub fn paint_rulers(ctx: &mut druid::PaintCtx, port: Rect, to_widget: Affine, base: f64) {
+	let spacing = adaptive_spacing(base, to_widget) * MAJOR_EVERY;
+	let scale = scale_of(to_widget);
+	if spacing <= 0.0 {
+		return;
+	}
+	let origin = to_widget * Point::ORIGIN;
+	let step = spacing * scale;
+	let background = Color::grey8(245);
+	let tick = Color::grey8(120);
+
+	let top = Rect::new(port.x0, port.y0, port.x1, port.y0 + RULER_SIZE);
+	let left = Rect::new(port.x0, port.y0, port.x0 + RULER_SIZE, port.y1);
+	ctx.fill(top, &background);
+	ctx.fill(left, &background);
+
+	let mut x = origin.x + ((port.x0 - origin.x) / step).floor() * step;
+	while x <= port.x1 {
+		ctx.stroke(Line::new((x, top.y0), (x, top.y1)), &tick, 1.0);
+		let units = ((x - origin.x) / scale).round();
+		paint_label(ctx, format!("{}", units), Point::new(x + 2.0, top.y0));
+		x += step;
+	}
+	let mut y = origin.y + ((port.y0 - origin.y) / step).floor() * step;
+	while y <= port.y1 {
+		ctx.stroke(Line::new((left.x0, y), (left.x1, y)), &tick, 1.0);
+		let units = ((y - origin.y) / scale).round();
+		paint_label(
+			ctx,
+			format!("{}", units),
+			Point::new(left.x0 + 2.0, y + 2.0),
+		);
+		y += step;
+	}
+
+	// Cover the corner where both rulers overlap
+	ctx.fill(
+		Rect::from_origin_size(port.origin(), (RULER_SIZE, RULER_SIZE)),
+		&background,
+	);
+}
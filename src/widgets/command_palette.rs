@@ -0,0 +1,90 @@
+//! A searchable list of every command in the application, opened in its own window.
+use druid::{
+	commands,
+	widget::{Button, Controller, Either, Flex, Scroll, TextBox},
+	Env, Event, EventCtx, KbKey, Target, Widget, WidgetExt,
+};
+
+use super::graphics_data::GraphicsData;
+use crate::app_commands::{AppCommand, COMMANDS};
+
+pub fn command_palette() -> impl Widget<GraphicsData> {
+	let mut list = Flex::column();
+	for command in COMMANDS {
+		list.add_child(Either::new(
+			move |data: &GraphicsData, _env| command.matches(&data.palette_query),
+			palette_entry(command),
+			Flex::column(),
+		));
+	}
+	Flex::column()
+		.with_child(
+			TextBox::new()
+				.with_placeholder("Search commands")
+				.controller(PaletteInput)
+				.lens(GraphicsData::palette_query)
+				.expand_width(),
+		)
+		.with_flex_child(Scroll::new(list).vertical(), 1.0)
+		.padding(8.0)
+}
+
+// A button that runs the command and closes the palette
+fn palette_entry(command: &'static AppCommand) -> impl Widget<GraphicsData> {
+	Button::new(move |data: &GraphicsData, _env: &Env| {
+		let keys = data.keymap.describe(command.selector);
+		if keys.is_empty() {
+			command.name.to_string()
+		} else {
+			format!("{}  ({})", command.name, keys)
+		}
+	})
+	.on_click(move |ctx, data: &mut GraphicsData, _env| {
+		data.palette_query.clear();
+		run_command(ctx, command);
+	})
+	.expand_width()
+}
+
+fn run_command(ctx: &mut EventCtx, command: &AppCommand) {
+	ctx.submit_command(command.selector.to(Target::Global));
+	ctx.submit_command(commands::CLOSE_WINDOW.to(ctx.window_id()));
+}
+
+// Focuses the search box when the palette opens, Enter runs the first match of a search and Escape
+// closes it
+struct PaletteInput;
+
+impl<W: Widget<String>> Controller<String, W> for PaletteInput {
+	fn event(
+		&mut self,
+		child: &mut W,
+		ctx: &mut EventCtx,
+		event: &Event,
+		data: &mut String,
+		env: &Env,
+	) {
+		match event {
+			Event::WindowConnected => ctx.request_focus(),
+			Event::KeyDown(e) if e.key == KbKey::Enter => {
+				// Every command matches an empty search, so there is nothing to pick yet
+				let command = if data.trim().is_empty() {
+					None
+				} else {
+					COMMANDS.iter().find(|command| command.matches(data))
+				};
+				if let Some(command) = command {
+					data.clear();
+					run_command(ctx, command);
+				}
+				ctx.set_handled();
+			}
+			Event::KeyDown(e) if e.key == KbKey::Escape => {
+				data.clear();
+				ctx.submit_command(commands::CLOSE_WINDOW.to(ctx.window_id()));
+				ctx.set_handled();
+			}
+			_ => child.event(ctx, event, data, env),
+		}
+	}
+}
@@ -3,50 +3,316 @@
 /// Composed widgets are any that are built using the existing WidgetExt functions
 /// and any Control wrappers. Im putting them here since they dont have a regular
 /// struct organizational system to use instead.
-use druid::text::format::ParseFormatter;
-use druid::{widget::*, Data, Lens, LensExt, Widget, WidgetExt};
+use druid::text::{
+	format::{Formatter, Validation, ValidationError},
+	Selection,
+};
+use druid::{theme, widget::*, Data, Lens, LensExt, RenderContext, Selector, Widget, WidgetExt};
 use tool::ToolObj;
 
-use crate::draw_tools::{tool, FractalLineTool, SelectionTool};
+use crate::app_commands::{
+	CONVERT_TO_PATH, INTERSECT_SELECTED, RESET_ROTATION, SELECT_EDIT_TOOL,
+	SELECT_FRACTAL_LINE_TOOL, SELECT_SELECTION_TOOL, SHOW_COMMAND_PALETTE, SHOW_EXPORT_ANIMATION,
+	SHOW_PREFERENCES, SUBTRACT_SELECTED, UNION_SELECTED, ZOOM_TO_ACTUAL_SIZE, ZOOM_TO_FIT,
+	ZOOM_TO_SELECTION,
+};
+use crate::draw_tools::snapping::SnapSettings;
+use crate::draw_tools::{tool, EditTool, FractalLineTool, SelectionTool};
 
-use super::graphics_data::GraphicsData;
+use super::{
+	canvas_overlays::CanvasOverlays, graphics_data::GraphicsData, preset_menu::preset_menu,
+	tool_preview::ApplyToSelection, tooltip::Tooltip,
+};
 
 pub fn settings_menu() -> impl Widget<GraphicsData> {
-	tool::ToolObj::matcher()
-		.fractal_line_tool(FractalLineTool::get_configuration())
-		.selection_tool(SelectionTool::get_configuration())
-		.lens(GraphicsData::tool)
+	Flex::column()
+		.with_child(Either::new(
+			|data: &GraphicsData, _env| matches!(data.tool, ToolObj::FractalLineTool(_)),
+			Flex::column().with_child(preset_menu()).with_child(
+				Checkbox::new("Apply to selection").lens(GraphicsData::apply_to_selection),
+			),
+			SizedBox::empty(),
+		))
+		.with_child(
+			tool::ToolObj::matcher()
+				.fractal_line_tool(FractalLineTool::get_configuration())
+				.selection_tool(SelectionTool::get_configuration())
+				.edit_tool(EditTool::get_configuration())
+				.lens(GraphicsData::tool),
+		)
+		.controller(ApplyToSelection::default())
 }
 
-pub fn tool_selection_button(tool: ToolObj, name: &str) -> impl Widget<GraphicsData> {
-	Button::new(name).on_click(move |ctx, data: &mut GraphicsData, _env| {
-		data.tool.disable(&mut data.objects);
-		data.tool = tool;
-		data.tool.enable(&mut data.objects);
-		ctx.request_layout();
-	})
+pub fn toolbar() -> impl Widget<GraphicsData> {
+	Flex::column()
+		.with_child(tool_button(
+			include_str!("../../assets/icons/fractal_line.svg"),
+			"Fractal Line Tool",
+			SELECT_FRACTAL_LINE_TOOL,
+			|tool| matches!(tool, ToolObj::FractalLineTool(_)),
+		))
+		.with_child(tool_button(
+			include_str!("../../assets/icons/selection.svg"),
+			"Selection Tool",
+			SELECT_SELECTION_TOOL,
+			|tool| matches!(tool, ToolObj::SelectionTool(_)),
+		))
+		.with_child(tool_button(
+			include_str!("../../assets/icons/edit.svg"),
+			"Edit Tool",
+			SELECT_EDIT_TOOL,
+			|tool| matches!(tool, ToolObj::EditTool(_)),
+		))
 }
 
-pub fn integer_stepper<T: Data, L: Lens<T, i32> + 'static>(
-	min: i32,
-	max: i32,
-	l: L,
-) -> impl Widget<T> {
-	Flex::row()
-		.with_child(TextBox::new().with_formatter(ParseFormatter::new()))
-		.with_child(Stepper::new().with_range(min as f64, max as f64))
-		.lens(l.map(|val| *val as f64, |val, new| *val = new as i32))
+// An icon that switches to a tool, highlighted while that tool is active
+fn tool_button(
+	icon: &str,
+	name: &'static str,
+	command: Selector,
+	is_active: fn(&ToolObj) -> bool,
+) -> impl Widget<GraphicsData> {
+	let icon = icon.parse::<SvgData>().unwrap_or_else(|e| {
+		log::error!("Bad icon for {}: {}", name, e);
+		SvgData::empty()
+	});
+	Svg::new(icon)
+		.fix_size(24.0, 24.0)
+		.padding(6.0)
+		.background(Painter::new(move |ctx, data: &GraphicsData, env| {
+			let bounds = ctx.size().to_rect().to_rounded_rect(4.0);
+			if is_active(&data.tool) {
+				ctx.fill(bounds, &env.get(theme::SELECTION_COLOR));
+			} else if ctx.is_hot() {
+				ctx.fill(bounds, &env.get(theme::BUTTON_LIGHT));
+			}
+		}))
+		.on_click(move |ctx, _data: &mut GraphicsData, _env| ctx.submit_command(command))
+		.controller(Tooltip::new(move |data: &GraphicsData, _env| {
+			let keys = data.keymap.describe(command);
+			if keys.is_empty() {
+				name.to_string()
+			} else {
+				format!("{} ({})", name, keys)
+			}
+		}))
 }
 
-pub fn slider_with_label<T: Data, L: Lens<T, f64> + 'static>(
-	min: f64,
-	max: f64,
-	l: L,
-) -> impl Widget<T> {
-	Flex::row()
+pub fn animation_controls() -> impl Widget<GraphicsData> {
+	Flex::column()
+		.with_child(Checkbox::new("Animate").lens(GraphicsData::animating))
+		.with_child(command_button("Export Animation", SHOW_EXPORT_ANIMATION))
+}
+
+pub fn snap_settings() -> impl Widget<GraphicsData> {
+	Flex::column()
+		.with_child(Label::new("Snapping"))
+		.with_child(Checkbox::new("Grid").lens(SnapSettings::grid))
+		.with_child(
+			Param::new("Grid Size", 1.0, 100.0, 20.0)
+				.unit("px")
+				.tooltip("Spacing of the grid that points snap to")
+				.slider(SnapSettings::grid_size),
+		)
+		.with_child(Checkbox::new("Endpoints").lens(SnapSettings::endpoints))
+		.with_child(Checkbox::new("Midpoints").lens(SnapSettings::midpoints))
+		.with_child(Checkbox::new("Intersections").lens(SnapSettings::intersections))
+		.with_child(
+			Param::new("Angle Step", 1.0, 90.0, 15.0)
+				.unit("°")
+				.tooltip("Angles lines snap to while Shift is held")
+				.slider(SnapSettings::angle_step),
+		)
+		.lens(GraphicsData::snap)
+}
+
+pub fn overlay_settings() -> impl Widget<GraphicsData> {
+	Flex::column()
+		.with_child(Label::new("View"))
+		.with_child(Checkbox::new("Grid").lens(CanvasOverlays::grid))
+		.with_child(Checkbox::new("Rulers").lens(CanvasOverlays::rulers))
+		.with_child(Checkbox::new("Origin").lens(CanvasOverlays::origin))
+		.lens(GraphicsData::overlays)
+}
+
+pub fn view_controls() -> impl Widget<GraphicsData> {
+	Flex::column()
+		.with_child(command_button("Zoom to Fit", ZOOM_TO_FIT))
+		.with_child(command_button("Zoom to Selection", ZOOM_TO_SELECTION))
+		.with_child(command_button("100%", ZOOM_TO_ACTUAL_SIZE))
+		.with_child(command_button("Reset Rotation", RESET_ROTATION))
+		.with_child(Checkbox::new("Smooth Zoom").lens(GraphicsData::smooth_zoom))
+		.with_child(command_button("Commands…", SHOW_COMMAND_PALETTE))
+		.with_child(command_button("Preferences…", SHOW_PREFERENCES))
+}
+
+pub fn path_controls() -> impl Widget<GraphicsData> {
+	Flex::column()
+		.with_child(command_button("Convert to Path", CONVERT_TO_PATH))
 		.with_child(
-			TextBox::new().with_formatter(ParseFormatter::with_format_fn(|v| format!("{:.2}", v))),
+			Flex::row()
+				.with_child(command_button("Union", UNION_SELECTED))
+				.with_child(command_button("Intersect", INTERSECT_SELECTED))
+				.with_child(command_button("Subtract", SUBTRACT_SELECTED)),
 		)
-		.with_child(Slider::new().with_range(min as f64, max as f64))
-		.lens(l)
+}
+
+pub fn command_button(name: &str, command: Selector) -> impl Widget<GraphicsData> {
+	Button::new(name)
+		.on_click(move |ctx, _data: &mut GraphicsData, _env| ctx.submit_command(command))
+}
+
+// Width of the name column so that the rows of a panel line up
+const PARAM_LABEL_WIDTH: f64 = 110.0;
+const PARAM_VALUE_WIDTH: f64 = 56.0;
+
+/// A numeric setting along with its range and default, used to build consistent editing rows
+#[derive(Clone, Debug)]
+pub struct Param {
+	name: String,
+	unit: &'static str,
+	min: f64,
+	max: f64,
+	default: f64,
+	log_scale: bool,
+	tooltip: &'static str,
+}
+
+impl Param {
+	pub fn new(name: impl Into<String>, min: f64, max: f64, default: f64) -> Self {
+		Self {
+			name: name.into(),
+			unit: "",
+			min,
+			max,
+			default,
+			log_scale: false,
+			tooltip: "",
+		}
+	}
+
+	/// The same parameter under another name, for settings that share a range
+	pub fn named(mut self, name: impl Into<String>) -> Self {
+		self.name = name.into();
+		self
+	}
+
+	/// The unit shown after the value, eg. "px"
+	pub fn unit(mut self, unit: &'static str) -> Self {
+		self.unit = unit;
+		self
+	}
+
+	/// Space the slider logarithmically, for values spanning orders of magnitude. The range must be positive
+	pub fn log_scale(mut self) -> Self {
+		debug_assert!(
+			self.min > 0.0,
+			"{} has a log scale but a minimum of {}",
+			self.name,
+			self.min
+		);
+		self.log_scale = true;
+		self
+	}
+
+	pub fn tooltip(mut self, tooltip: &'static str) -> Self {
+		self.tooltip = tooltip;
+		self
+	}
+
+	/// Clamp a value into the parameter's range
+	pub fn clamp(&self, value: f64) -> f64 {
+		if value.is_nan() {
+			self.default
+		} else {
+			value.clamp(self.min, self.max)
+		}
+	}
+
+	/// A row with a slider for a real valued setting
+	pub fn slider<T: Data, L: Lens<T, f64> + 'static>(self, lens: L) -> impl Widget<T> {
+		let slider: Box<dyn Widget<f64>> = if self.log_scale {
+			Box::new(Slider::new().with_range(self.min.ln(), self.max.ln()).lens(
+				druid::lens::Map::new(|v: &f64| v.ln(), |v: &mut f64, new: f64| *v = new.exp()),
+			))
+		} else {
+			Box::new(Slider::new().with_range(self.min, self.max))
+		};
+		let lens = self.clamped(lens);
+		self.row(slider, 2).lens(lens)
+	}
+
+	/// A row with a stepper for an integer setting
+	pub fn stepper<T: Data, L: Lens<T, i32> + 'static>(self, lens: L) -> impl Widget<T> {
+		let stepper = Stepper::new().with_range(self.min, self.max).with_step(1.0);
+		let lens = self.clamped(lens.map(|v| *v as f64, |v, new| *v = new.round() as i32));
+		self.row(stepper, 0).lens(lens)
+	}
+
+	// Keeps the value within range however it changes, whether from the control, the text box or
+	// from outside the row such as a preset being applied
+	fn clamped<T, L: Lens<T, f64>>(&self, lens: L) -> impl Lens<T, f64> {
+		let (get, put) = (self.clone(), self.clone());
+		lens.map(move |v| get.clamp(*v), move |v, new| *v = put.clamp(new))
+	}
+
+	// The name, control, typed value, unit and reset button of a setting
+	fn row(self, control: impl Widget<f64> + 'static, decimals: usize) -> impl Widget<f64> {
+		let tooltip = self.tooltip;
+		let default = self.default;
+		Flex::row()
+			.with_child(
+				Label::new(self.name.clone())
+					.fix_width(PARAM_LABEL_WIDTH)
+					.controller(Tooltip::new(move |_: &f64, _env| tooltip.to_string())),
+			)
+			.with_flex_child(control, 1.0)
+			.with_child(
+				TextBox::new()
+					.with_formatter(ParamFormatter {
+						decimals,
+						param: self.clone(),
+					})
+					.fix_width(PARAM_VALUE_WIDTH),
+			)
+			.with_child(Label::new(self.unit))
+			.with_child(
+				Button::new("↺")
+					.on_click(move |_ctx, value: &mut f64, _env| *value = default)
+					.controller(Tooltip::new(move |_: &f64, _env| {
+						format!("Reset to {}", default)
+					})),
+			)
+	}
+}
+
+// Formats a parameter for its text box and clamps whatever is typed into its range
+struct ParamFormatter {
+	param: Param,
+	decimals: usize,
+}
+
+impl Formatter<f64> for ParamFormatter {
+	fn format(&self, value: &f64) -> String {
+		format!("{:.*}", self.decimals, value)
+	}
+
+	fn validate_partial_input(&self, input: &str, _sel: &Selection) -> Validation {
+		match input.trim() {
+			// Allow the beginnings of numbers that don't parse on their own yet
+			"" | "-" | "." | "-." => Validation::success(),
+			text => match text.parse::<f64>() {
+				Ok(_) => Validation::success(),
+				Err(e) => Validation::failure(e),
+			},
+		}
+	}
+
+	fn value(&self, input: &str) -> Result<f64, ValidationError> {
+		input
+			.trim()
+			.parse::<f64>()
+			.map(|v| self.param.clamp(v))
+			.map_err(ValidationError::new)
+	}
 }
@@ -0,0 +1,119 @@
+use druid::{kurbo::BezPath, Color, Event, Point, Rect, RenderContext, Size, Widget};
+
+use crate::render_objects::curve::{Curve, CURVE_POINTS};
+
+/// A small graph of a Curve, control points are set by clicking or dragging.
+pub struct CurveEditor {
+	size: Size,
+}
+
+impl CurveEditor {
+	pub fn new() -> Self {
+		Self {
+			size: Size::new(150.0, 60.0),
+		}
+	}
+
+	fn point_for(&self, index: usize, value: f64) -> Point {
+		Point::new(
+			index as f64 / (CURVE_POINTS - 1) as f64 * self.size.width,
+			(1.0 - value) * self.size.height,
+		)
+	}
+
+	fn set_from_pos(&self, pos: Point, data: &mut Curve) {
+		let index = (pos.x / self.size.width * (CURVE_POINTS - 1) as f64).round();
+		let index = index.clamp(0.0, (CURVE_POINTS - 1) as f64) as usize;
+		data.0[index] = (1.0 - pos.y / self.size.height).clamp(0.0, 1.0);
+	}
+}
+
+impl Widget<Curve> for CurveEditor {
+	fn event(
+		&mut self,
+		ctx: &mut druid::EventCtx,
+		event: &Event,
+		data: &mut Curve,
+		_env: &druid::Env,
+	) {
+		match event {
+			Event::MouseDown(e) => {
+				ctx.set_active(true);
+				self.set_from_pos(e.pos, data);
+				ctx.set_handled();
+			}
+			Event::MouseMove(e) => {
+				if ctx.is_active() {
+					self.set_from_pos(e.pos, data);
+					ctx.set_handled();
+				}
+			}
+			Event::MouseUp(_) => {
+				if ctx.is_active() {
+					ctx.set_active(false);
+					ctx.set_handled();
+				}
+			}
+			_ => (),
+		}
+	}
+
+	fn lifecycle(
+		&mut self,
+		_ctx: &mut druid::LifeCycleCtx,
+		_event: &druid::LifeCycle,
+		_data: &Curve,
+		_env: &druid::Env,
+	) {
+	}
+
+	fn update(
+		&mut self,
+		ctx: &mut druid::UpdateCtx,
+		old_data: &Curve,
+		data: &Curve,
+		_env: &druid::Env,
+	) {
+		if old_data != data {
+			ctx.request_paint();
+		}
+	}
+
+	fn layout(
+		&mut self,
+		_ctx: &mut druid::LayoutCtx,
+		bc: &druid::BoxConstraints,
+		_data: &Curve,
+		_env: &druid::Env,
+	) -> Size {
+		self.size = bc.constrain(Size::new(150.0, 60.0));
+		self.size
+	}
+
+	fn paint(&mut self, ctx: &mut druid::PaintCtx, data: &Curve, _env: &druid::Env) {
+		ctx.fill(self.size.to_rect(), &Color::WHITE);
+		ctx.stroke(self.size.to_rect(), &Color::grey(0.5), 1.0);
+
+		let mut path = BezPath::new();
+		for (i, value) in data.0.iter().enumerate() {
+			let p = self.point_for(i, *value);
+			if i == 0 {
+				path.move_to(p);
+			} else {
+				path.line_to(p);
+			}
+		}
+		ctx.stroke(path, &Color::BLACK, 1.0);
+
+		for (i, value) in data.0.iter().enumerate() {
+			let p = self.point_for(i, *value);
+			ctx.fill(Rect::from_center_size(p, Size::new(4.0, 4.0)), &Color::RED);
+		}
+	}
+}
+
+impl Default for CurveEditor {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -1,7 +1,15 @@
+use std::sync::Arc;
+
+use super::canvas_overlays::CanvasOverlays;
+use crate::app_commands::Keymap;
+use crate::draw_tools::presets::PresetLibrary;
+use crate::draw_tools::snapping::SnapSettings;
 use crate::draw_tools::{FractalLineTool, Tool};
-use crate::render_objects::RenderObject;
+use crate::preferences::Preferences;
+use crate::render_objects::snap_target::SnapTarget;
+use crate::render_objects::{ObjectId, RenderObject};
 use druid::im::Vector;
-use druid::{Affine, Data, Lens, Vec2};
+use druid::{Affine, Data, Lens, Point, Rect, Size, Vec2};
 
 #[derive(Data, Clone, Lens)]
 pub struct GraphicsData {
@@ -9,8 +17,35 @@ pub struct GraphicsData {
 	pub preview: Option<RenderObject>,
 	pub tool: Tool,
 	pub transform: Affine,
+	// Seconds of animation that have played
+	pub time: f64,
+	pub animating: bool,
+	pub snap: SnapSettings,
+	// The snap target under the pointer, shown to the user
+	pub snap_target: Option<SnapTarget>,
+	pub overlays: CanvasOverlays,
+	// Ease zooming over several frames instead of jumping straight to the new scale
+	pub smooth_zoom: bool,
+	pub keymap: Arc<Keymap>,
+	// Snapshots of the objects before each change, most recent last
+	pub undo_stack: Vector<Vector<RenderObject>>,
+	pub redo_stack: Vector<Vector<RenderObject>>,
+	// Text typed into the command palette
+	pub palette_query: String,
+	pub presets: PresetLibrary,
+	pub preferences: Preferences,
+	// Changes to the fractal line tool's settings also restyle the selected objects
+	pub apply_to_selection: bool,
 }
 
+/// The number of changes that can be undone
+pub const UNDO_LIMIT: usize = 100;
+
+/// The furthest out the view can be zoomed
+pub const MIN_SCALE: f64 = 0.01;
+/// The furthest in the view can be zoomed
+pub const MAX_SCALE: f64 = 100.0;
+
 impl GraphicsData {
 	pub fn new() -> Self {
 		Self {
@@ -18,6 +53,66 @@ impl GraphicsData {
 			preview: None,
 			tool: Tool::FractalLineTool(FractalLineTool::new()),
 			transform: Affine::default(),
+			time: 0.0,
+			animating: false,
+			snap: SnapSettings::default(),
+			snap_target: None,
+			overlays: CanvasOverlays::default(),
+			smooth_zoom: true,
+			keymap: Arc::new(Keymap::default()),
+			undo_stack: Vector::new(),
+			redo_stack: Vector::new(),
+			palette_query: String::new(),
+			presets: PresetLibrary::default(),
+			preferences: Preferences::default(),
+			apply_to_selection: false,
+		}
+	}
+
+	/// The object with the given id, if it is still in the scene
+	pub fn object(&self, id: ObjectId) -> Option<&RenderObject> {
+		self.objects.iter().find(|object| object.id == id)
+	}
+
+	pub fn object_mut(&mut self, id: ObjectId) -> Option<&mut RenderObject> {
+		self.objects.iter_mut().find(|object| object.id == id)
+	}
+
+	/// Where the object with the given id currently is in the drawing order
+	pub fn index_of(&self, id: ObjectId) -> Option<usize> {
+		self.objects.iter().position(|object| object.id == id)
+	}
+
+	/// Switch to a different tool, letting both tools clean up after themselves
+	pub fn set_tool(&mut self, tool: Tool) {
+		self.tool.disable(&mut self.objects);
+		self.tool = tool;
+		self.tool.enable(&mut self.objects);
+	}
+
+	/// Record the objects as they were before a change, if anything changed
+	pub fn checkpoint(&mut self, previous: Vector<RenderObject>) {
+		if previous.same(&self.objects) {
+			return;
+		}
+		self.undo_stack.push_back(previous);
+		if self.undo_stack.len() > UNDO_LIMIT {
+			self.undo_stack.pop_front();
+		}
+		self.redo_stack.clear();
+	}
+
+	pub fn undo(&mut self) {
+		if let Some(previous) = self.undo_stack.pop_back() {
+			let current = std::mem::replace(&mut self.objects, previous);
+			self.redo_stack.push_back(current);
+		}
+	}
+
+	pub fn redo(&mut self) {
+		if let Some(next) = self.redo_stack.pop_back() {
+			let current = std::mem::replace(&mut self.objects, next);
+			self.undo_stack.push_back(current);
 		}
 	}
 
@@ -44,6 +139,83 @@ impl GraphicsData {
 		Affine::new(coeff)
 	}
 
+	// The rotation of the view in radians
+	pub fn get_rotation(&self) -> f64 {
+		let coeff = self.transform.as_coeffs();
+		coeff[1].atan2(coeff[0])
+	}
+
+	// The zoom factor of the view, 1.0 being one canvas unit per pixel
+	pub fn get_scale(&self) -> f64 {
+		self.transform.determinant().abs().sqrt()
+	}
+
+	// Sets the transform so that canvas_point is shown at widget_point with the given rotation and
+	// scale, keeping the scale within MIN_SCALE and MAX_SCALE
+	pub fn set_view(
+		&mut self,
+		widget_point: Point,
+		canvas_point: Point,
+		rotation: f64,
+		scale: f64,
+	) {
+		let scale = scale.clamp(MIN_SCALE, MAX_SCALE);
+		self.transform = Affine::translate(widget_point.to_vec2())
+			* Affine::rotate(rotation)
+			* Affine::scale(scale)
+			* Affine::translate(-canvas_point.to_vec2());
+	}
+
+	// Moves the view by a distance in widget space
+	pub fn pan_by(&mut self, delta: Vec2) {
+		self.transform *=
+			Affine::translate((self.get_rot_scale().inverse() * delta.to_point()).to_vec2());
+	}
+
+	// Zooms and centers the view so that rect in canvas space fills a viewport of the given size,
+	// keeping the current rotation.
+	pub fn zoom_to_rect(&mut self, rect: Rect, port: Size) {
+		let rotation = self.get_rotation();
+		let (sin, cos) = rotation.sin_cos();
+		// Size of the rect once it has been rotated into widget space
+		let width = (rect.width() * cos).abs() + (rect.height() * sin).abs();
+		let height = (rect.width() * sin).abs() + (rect.height() * cos).abs();
+		if width <= 0.0 || height <= 0.0 {
+			self.set_view(
+				port.to_rect().center(),
+				rect.center(),
+				rotation,
+				self.get_scale(),
+			);
+			return;
+		}
+		// Leave a small margin around the edge so nothing touches the border
+		let scale = (port.width / width).min(port.height / height) * 0.9;
+		self.set_view(port.to_rect().center(), rect.center(), rotation, scale);
+	}
+
+	// The tight bounds in canvas space of every object, or only the selected ones
+	pub fn objects_bounds(&self, selected_only: bool) -> Option<Rect> {
+		self.objects
+			.iter()
+			.filter(|obj| !selected_only || obj.is_selected())
+			.map(|obj| obj.exact_bounds())
+			.reduce(|acc, rect| acc.union(rect))
+	}
+
+	// Zooms by a factor around a point in widget space, keeping the scale within MIN_SCALE and MAX_SCALE
+	pub fn zoom_around(&mut self, widget_point: Point, factor: f64) {
+		let scale = self.get_scale();
+		if scale <= 0.0 || !factor.is_finite() {
+			return;
+		}
+		let clamped = (scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+		self.scale_around_point(
+			(self.transform.inverse() * widget_point).to_vec2(),
+			clamped / scale,
+		);
+	}
+
 	// Scales the transform relative to a point p in canvas space.
 	pub fn scale_around_point(&mut self, p: Vec2, s: f64) {
 		self.transform *= Affine::translate(p) * Affine::scale(s) * Affine::translate(p).inverse();
@@ -1,6 +1,60 @@
-use druid::{im::Vector, Affine, Color, Data, Event, Point, Rect, RenderContext, Size, Widget};
+use std::time::{Duration, Instant};
 
-use super::graphics_data::GraphicsData;
+use druid::{
+	im::Vector, Affine, Color, Cursor, Data, Event, FileDialogOptions, FileSpec, Point, Rect,
+	RenderContext, Size, Vec2, Widget, WindowDesc,
+};
+
+use super::{
+	canvas_overlays,
+	command_palette::command_palette,
+	graphics_data::GraphicsData,
+	move_cursor::move_cursor,
+	preferences_dialog::preferences_dialog,
+	scene_diff::{self, Invalidation},
+};
+use crate::{
+	app_commands::*,
+	draw_tools::{
+		snapping::Snapper, tool::ToolCursor, EditTool, FractalLineTool, SelectionTool, Tool,
+	},
+	export::EXPORT_ANIMATION,
+	path_ops::{self, BooleanOp, Simplification},
+	preferences::{BACKGROUND_COLOR, COLLISION_TOLERANCE, WHEEL_ZOOM_SENSITIVITY, ZOOM_STEP},
+	render_objects::{
+		animation::ANIMATION_TIME,
+		drawable::{POINTER_ENTERED, POINTER_LEFT},
+		ObjectId, RenderObject,
+	},
+};
+
+// Distance in pixels the arrow keys move the view
+const NUDGE_DISTANCE: f64 = 20.0;
+// Time constant in seconds of smooth zooming, smaller values settle faster
+const ZOOM_EASING: f64 = 0.06;
+
+// Distance in screen pixels from an object within which the pointer counts as over it
+const HIT_RADIUS: f64 = 4.0;
+// Width in screen pixels of the outline drawn around the object under the pointer
+const HOVER_WIDTH: f64 = 1.5;
+const HOVER_COLOR: Color = Color::rgba8(0, 120, 215, 160);
+// Holding a tool key for longer than this switches back to the previous tool when it is released
+const TOOL_HOLD_THRESHOLD: Duration = Duration::from_millis(300);
+
+// A zoom that is being eased in over several animation frames
+struct ZoomAnimation {
+	// Widget space point that stays fixed while zooming
+	anchor: Point,
+	// Natural log of the zoom factor still to be applied
+	remaining: f64,
+}
+
+// A tool key that is held down, so the tool can be switched back when it is released
+struct HeldToolKey {
+	code: druid::Code,
+	pressed: Instant,
+	previous: Tool,
+}
 
 #[derive(PartialEq)]
 enum GraphicsWidgetState {
@@ -13,6 +67,18 @@ pub struct GraphicsWidget {
 	port: Rect,
 	state: GraphicsWidgetState,
 	shift_held: bool,
+	space_held: bool,
+	mouse_pos: Point,
+	zoom_animation: Option<ZoomAnimation>,
+	// The objects as they were when the mouse was pressed, used to record undo history
+	gesture_start: Option<Vector<RenderObject>>,
+	held_tool_key: Option<HeldToolKey>,
+	// The topmost object under the pointer
+	hovered: Option<ObjectId>,
+	// The object that handled the last MouseDown, which receives mouse events until release
+	captured: Option<ObjectId>,
+	// Made once the window is connected, as custom cursors belong to a window
+	move_cursor: Option<Cursor>,
 }
 
 impl GraphicsWidget {
@@ -21,6 +87,277 @@ impl GraphicsWidget {
 			port: Default::default(),
 			state: GraphicsWidgetState::Default,
 			shift_held: false,
+			space_held: false,
+			mouse_pos: Point::ZERO,
+			zoom_animation: None,
+			gesture_start: None,
+			held_tool_key: None,
+			hovered: None,
+			captured: None,
+			move_cursor: None,
+		}
+	}
+
+	// Zoom around a widget space point by e^log_factor, easing it in if smooth zoom is enabled
+	fn zoom(
+		&mut self,
+		ctx: &mut druid::EventCtx,
+		data: &mut GraphicsData,
+		anchor: Point,
+		log_factor: f64,
+	) {
+		if !data.smooth_zoom {
+			data.zoom_around(anchor, log_factor.exp());
+			return;
+		}
+		let remaining = self
+			.zoom_animation
+			.as_ref()
+			.map_or(0.0, |anim| anim.remaining);
+		self.zoom_animation = Some(ZoomAnimation {
+			anchor,
+			remaining: remaining + log_factor,
+		});
+		ctx.request_anim_frame();
+	}
+
+	fn step_zoom_animation(
+		&mut self,
+		ctx: &mut druid::EventCtx,
+		data: &mut GraphicsData,
+		interval: u64,
+	) {
+		if let Some(anim) = &mut self.zoom_animation {
+			let dt = interval as f64 / 1e9;
+			let step = if anim.remaining.abs() < 1e-3 {
+				anim.remaining
+			} else {
+				anim.remaining * (1.0 - (-dt / ZOOM_EASING).exp())
+			};
+			data.zoom_around(anim.anchor, step.exp());
+			anim.remaining -= step;
+			if anim.remaining == 0.0 {
+				self.zoom_animation = None;
+			} else {
+				ctx.request_anim_frame();
+			}
+		}
+	}
+
+	// The canvas space point shown in the middle of the widget
+	fn view_center(&self, data: &GraphicsData) -> Point {
+		data.get_trans_to_widget().inverse() * self.port.center()
+	}
+
+	fn handle_command(
+		&mut self,
+		ctx: &mut druid::EventCtx,
+		command: &druid::Command,
+		data: &mut GraphicsData,
+		env: &druid::Env,
+	) -> bool {
+		let center = self.view_center(data);
+		if command.is(ZOOM_TO_FIT) {
+			if let Some(bounds) = data.objects_bounds(false) {
+				data.zoom_to_rect(bounds, self.port.size());
+			}
+		} else if command.is(ZOOM_TO_SELECTION) {
+			if let Some(bounds) = data.objects_bounds(true) {
+				data.zoom_to_rect(bounds, self.port.size());
+			}
+		} else if command.is(ZOOM_TO_ACTUAL_SIZE) {
+			data.set_view(self.port.center(), center, data.get_rotation(), 1.0);
+		} else if command.is(RESET_ROTATION) {
+			data.set_view(self.port.center(), center, 0.0, data.get_scale());
+		} else if command.is(ZOOM_IN) {
+			self.zoom(ctx, data, self.port.center(), env.get(ZOOM_STEP).ln());
+		} else if command.is(ZOOM_OUT) {
+			self.zoom(ctx, data, self.port.center(), -env.get(ZOOM_STEP).ln());
+		} else if command.is(PAN_LEFT) {
+			data.pan_by(Vec2::new(NUDGE_DISTANCE, 0.0));
+		} else if command.is(PAN_RIGHT) {
+			data.pan_by(Vec2::new(-NUDGE_DISTANCE, 0.0));
+		} else if command.is(PAN_UP) {
+			data.pan_by(Vec2::new(0.0, NUDGE_DISTANCE));
+		} else if command.is(PAN_DOWN) {
+			data.pan_by(Vec2::new(0.0, -NUDGE_DISTANCE));
+		} else if command.is(TOGGLE_GRID) {
+			data.overlays.grid = !data.overlays.grid;
+		} else if command.is(TOGGLE_RULERS) {
+			data.overlays.rulers = !data.overlays.rulers;
+		} else if command.is(TOGGLE_ORIGIN) {
+			data.overlays.origin = !data.overlays.origin;
+		} else if command.is(TOGGLE_GRID_SNAPPING) {
+			data.snap.grid = !data.snap.grid;
+		} else if command.is(SELECT_FRACTAL_LINE_TOOL) {
+			let mut tool = FractalLineTool::default();
+			if let Some(preset) = data.presets.active_preset() {
+				tool.apply_preset(preset);
+			}
+			data.set_tool(Tool::FractalLineTool(tool));
+			ctx.request_layout();
+		} else if command.is(SELECT_SELECTION_TOOL) {
+			data.set_tool(Tool::SelectionTool(SelectionTool::default()));
+			ctx.request_layout();
+		} else if command.is(SELECT_EDIT_TOOL) {
+			data.set_tool(Tool::EditTool(EditTool::default()));
+			ctx.request_layout();
+		} else if command.is(DELETE_SELECTED) {
+			let before = data.objects.clone();
+			data.objects.retain(|object| !object.is_selected());
+			data.checkpoint(before);
+		} else if command.is(SELECT_ALL) {
+			let before = data.objects.clone();
+			data.objects.iter_mut().for_each(|object| object.select());
+			data.checkpoint(before);
+		} else if command.is(DESELECT_ALL) {
+			let before = data.objects.clone();
+			data.objects.iter_mut().for_each(|object| object.deselect());
+			data.checkpoint(before);
+		} else if command.is(CONVERT_TO_PATH) {
+			let before = data.objects.clone();
+			let simplification = Simplification::from_env(env);
+			path_ops::convert_selected(&mut data.objects, data.time, simplification);
+			data.checkpoint(before);
+		} else if let Some(op) = boolean_op(command) {
+			let before = data.objects.clone();
+			let simplification = Simplification::from_env(env);
+			path_ops::combine_selected(&mut data.objects, op, data.time, simplification);
+			data.checkpoint(before);
+		} else if command.is(UNDO) {
+			data.undo();
+		} else if command.is(REDO) {
+			data.redo();
+		} else if command.is(TOGGLE_ANIMATION) {
+			data.animating = !data.animating;
+		} else if command.is(SHOW_EXPORT_ANIMATION) {
+			ctx.submit_command(
+				druid::commands::SHOW_SAVE_PANEL.with(
+					FileDialogOptions::new()
+						.allowed_types(vec![FileSpec::new("SVG", &["svg"])])
+						.accept_command(EXPORT_ANIMATION),
+				),
+			);
+		} else if let Some(file) = command.get(EXPORT_ANIMATION) {
+			let svg = crate::export::animated_svg(&data.objects, Simplification::from_env(env));
+			if let Err(e) = std::fs::write(file.path(), svg) {
+				log::error!("Failed to export animation: {}", e);
+			}
+		} else if command.is(SHOW_PREFERENCES) {
+			ctx.new_window(
+				WindowDesc::new(preferences_dialog)
+					.title("Preferences")
+					.window_size((360.0, 420.0)),
+			);
+		} else if command.is(SHOW_COMMAND_PALETTE) {
+			ctx.new_window(
+				WindowDesc::new(command_palette)
+					.title("Commands")
+					.window_size((360.0, 480.0)),
+			);
+		} else {
+			return false;
+		}
+		true
+	}
+
+	// Deliver a canvas space pointer event to the objects.
+	//
+	// The topmost object under the pointer receives the event in its own coordinate space. An
+	// object that handles a MouseDown captures the pointer and receives every mouse event until
+	// the button is released. Objects are sent POINTER_ENTERED and POINTER_LEFT commands as the
+	// pointer moves over and off them. Tools only see events that no object handled.
+	fn dispatch_to_objects(
+		&mut self,
+		ctx: &mut druid::EventCtx,
+		event: &Event,
+		data: &mut GraphicsData,
+		env: &druid::Env,
+	) {
+		let pos = match event {
+			Event::MouseDown(e) | Event::MouseMove(e) | Event::MouseUp(e) => e.pos,
+			Event::Wheel(e) => e.pos,
+			_ => return,
+		};
+		let radius = HIT_RADIUS / data.get_scale();
+		let tolerance = env.get(COLLISION_TOLERANCE);
+		let hit = data
+			.objects
+			.iter()
+			.rev()
+			.find(|object| object.hit_test(pos, radius, tolerance, data.time))
+			.map(|object| object.id);
+
+		if let Some(id) = self.captured.or(hit) {
+			Self::send_to_object(ctx, event, data, id, env);
+			if ctx.is_handled() && matches!(event, Event::MouseDown(_)) {
+				self.captured = Some(id);
+			}
+		}
+		if let Event::MouseUp(_) = event {
+			self.captured = None;
+		}
+
+		// The hovered object doesn't change while another has captured the pointer
+		if self.captured.is_none() && hit != self.hovered {
+			if let Some(id) = self.hovered {
+				Self::send_to_object(ctx, &Event::Command(POINTER_LEFT.into()), data, id, env);
+			}
+			if let Some(id) = hit {
+				Self::send_to_object(ctx, &Event::Command(POINTER_ENTERED.into()), data, id, env);
+			}
+			// Repaint where the hover outline was and where it will be
+			let to_widget = data.get_trans_to_widget();
+			for object in [self.hovered, hit]
+				.iter()
+				.flatten()
+				.filter_map(|&id| data.object(id))
+			{
+				ctx.request_paint_rect(Self::hover_rect(object, to_widget));
+			}
+			self.hovered = hit;
+		}
+	}
+
+	// The widget space area covered by an object's hover outline
+	fn hover_rect(object: &RenderObject, to_widget: Affine) -> Rect {
+		object
+			.bounds_in(to_widget)
+			.inflate(HOVER_WIDTH, HOVER_WIDTH)
+	}
+
+	// Show what pressing the mouse at its current position would do
+	fn update_cursor(&self, ctx: &mut druid::EventCtx, data: &GraphicsData, env: &druid::Env) {
+		let cursor = if self.space_held || self.state != GraphicsWidgetState::Default {
+			Cursor::OpenHand
+		} else {
+			let pos = data.get_trans_to_widget().inverse() * self.mouse_pos;
+			match data
+				.tool
+				.cursor(pos, &data.objects, 1.0 / data.get_scale(), data.time, env)
+			{
+				ToolCursor::Arrow => Cursor::Arrow,
+				ToolCursor::Crosshair => Cursor::Crosshair,
+				// The closest builtin cursor stands in where custom ones aren't supported
+				ToolCursor::Move => self.move_cursor.clone().unwrap_or(Cursor::OpenHand),
+				ToolCursor::ResizeLeftRight => Cursor::ResizeLeftRight,
+				ToolCursor::ResizeUpDown => Cursor::ResizeUpDown,
+			}
+		};
+		ctx.set_cursor(&cursor);
+	}
+
+	fn send_to_object(
+		ctx: &mut druid::EventCtx,
+		event: &Event,
+		data: &mut GraphicsData,
+		id: ObjectId,
+		env: &druid::Env,
+	) {
+		if let Some(object) = data.object_mut(id) {
+			let context = object.clone();
+			let local = Self::adjust_event_by_transform(event.clone(), object.transform.inverse());
+			object.drawable.event(ctx, &local, env, &context);
 		}
 	}
 
@@ -29,11 +366,17 @@ impl GraphicsWidget {
 		ctx: &mut druid::EventCtx,
 		event: &Event,
 		data: &mut GraphicsData,
-		_env: &druid::Env,
+		env: &druid::Env,
 	) {
+		if let Event::MouseMove(e) = event {
+			self.mouse_pos = e.pos;
+		}
 		match event {
 			Event::MouseDown(e) => {
-				if e.button.is_middle() {
+				if e.button.is_left() && self.space_held {
+					self.state = GraphicsWidgetState::Panning(e.pos);
+					ctx.set_handled();
+				} else if e.button.is_middle() {
 					if self.shift_held {
 						self.state = GraphicsWidgetState::Rotating(e.pos);
 					} else {
@@ -45,9 +388,7 @@ impl GraphicsWidget {
 			Event::MouseMove(e) => match self.state {
 				GraphicsWidgetState::Default => (),
 				GraphicsWidgetState::Panning(p) => {
-					data.transform *= Affine::translate(
-						(data.get_rot_scale().inverse() * (e.pos - p).to_point()).to_vec2(),
-					);
+					data.pan_by(e.pos - p);
 					self.state = GraphicsWidgetState::Panning(e.pos);
 					ctx.set_handled();
 				}
@@ -65,36 +406,80 @@ impl GraphicsWidget {
 				}
 			},
 			Event::MouseUp(e) => {
-				if e.button.is_middle() {
+				let space_panning =
+					e.button.is_left() && matches!(self.state, GraphicsWidgetState::Panning(_));
+				if e.button.is_middle() || space_panning {
 					self.state = GraphicsWidgetState::Default;
 					ctx.set_handled();
 				}
 			}
-			Event::Wheel(e) => match e.wheel_delta.y.partial_cmp(&0.0) {
-				Some(cmp) => match cmp {
-					std::cmp::Ordering::Less => {
-						data.scale_around_point((data.transform.inverse() * e.pos).to_vec2(), 1.01)
-					}
-					std::cmp::Ordering::Equal => (),
-					std::cmp::Ordering::Greater => data.scale_around_point(
-						(data.transform.inverse() * e.pos).to_vec2(),
-						1.0 / 1.01,
-					),
-				},
-				None => todo!(),
-			},
+			Event::Wheel(e) => {
+				// Horizontal scrolling pans, vertical scrolling zooms in proportion to how far it moved
+				if e.wheel_delta.x != 0.0 {
+					data.pan_by(Vec2::new(-e.wheel_delta.x, 0.0));
+				}
+				if e.wheel_delta.y != 0.0 {
+					self.zoom(
+						ctx,
+						data,
+						e.pos,
+						-e.wheel_delta.y * env.get(WHEEL_ZOOM_SENSITIVITY),
+					);
+				}
+				ctx.set_handled();
+			}
+			Event::AnimFrame(interval) => self.step_zoom_animation(ctx, data, *interval),
 			Event::KeyDown(e) => {
 				if (e.code == druid::Code::ShiftLeft) | (e.code == druid::Code::ShiftRight) {
 					self.shift_held = true
 				}
+				if let Some(command) = data.keymap.lookup(e) {
+					if TOOL_COMMANDS.contains(&command) {
+						if e.repeat {
+							ctx.set_handled();
+							return;
+						}
+						self.held_tool_key = Some(HeldToolKey {
+							code: e.code,
+							pressed: Instant::now(),
+							previous: data.tool,
+						});
+					}
+					ctx.submit_command(command);
+					ctx.set_handled();
+				} else if e.code == druid::Code::Space {
+					self.space_held = true;
+					ctx.set_handled();
+				}
 			}
 			Event::KeyUp(e) => {
 				if (e.code == druid::Code::ShiftLeft) | (e.code == druid::Code::ShiftRight) {
 					self.shift_held = false
 				}
+				if e.code == druid::Code::Space {
+					self.space_held = false;
+					ctx.set_handled();
+				}
+				if let Some(held) = self.held_tool_key.take() {
+					if held.code != e.code {
+						self.held_tool_key = Some(held);
+					} else if held.pressed.elapsed() >= TOOL_HOLD_THRESHOLD {
+						data.set_tool(held.previous);
+						ctx.request_layout();
+						ctx.set_handled();
+					}
+				}
 			}
-			Event::Zoom(s) => {
-				data.transform *= Affine::scale(*s);
+			Event::Command(command) => {
+				if self.handle_command(ctx, command, data, env) {
+					ctx.set_handled();
+				}
+			}
+			Event::Zoom(delta) => {
+				// Pinch gestures carry no position, so zoom around the last known pointer position
+				let anchor = self.mouse_pos;
+				data.zoom_around(anchor, 1.0 + *delta);
+				ctx.set_handled();
 			}
 			_ => (),
 		}
@@ -136,32 +521,70 @@ impl Widget<GraphicsData> for GraphicsWidget {
 		data: &mut GraphicsData,
 		env: &druid::Env,
 	) {
+		let affects_cursor = matches!(
+			event,
+			Event::MouseDown(_)
+				| Event::MouseMove(_)
+				| Event::MouseUp(_)
+				| Event::KeyDown(_)
+				| Event::KeyUp(_)
+		);
 		self.handle_transformation_events(ctx, event, data, env);
 		if ctx.is_handled() {
+			if affects_cursor {
+				self.update_cursor(ctx, data, env);
+			}
 			return;
 		}
 		let trans_event =
 			&Self::adjust_event_by_transform(event.clone(), data.get_trans_to_widget().inverse());
-		data.tool.event(trans_event, ctx, &mut data.objects);
+		if let Event::MouseDown(_) = event {
+			self.gesture_start = Some(data.objects.clone());
+		}
+		self.dispatch_to_objects(ctx, trans_event, data, env);
+		let mut snapper = Snapper::new(
+			data.snap,
+			data.objects.clone(),
+			data.get_trans_to_widget(),
+			data.time,
+		);
+		if !ctx.is_handled() {
+			data.tool
+				.event(trans_event, ctx, &mut data.objects, &mut snapper, env);
+		}
+		if let Event::MouseDown(_) | Event::MouseMove(_) | Event::MouseUp(_) = event {
+			data.snap_target = snapper.active();
+		}
+		// Everything that happened between pressing and releasing the mouse is undone together
+		if let Event::MouseUp(_) = event {
+			if let Some(before) = self.gesture_start.take() {
+				data.checkpoint(before);
+			}
+		}
 		if !ctx.is_handled() {
 			#[allow(clippy::single_match)]
 			// We expect to match other expressions later, but this is the only one that matters now
 			match event {
 				Event::WindowConnected => {
 					ctx.request_focus();
+					self.move_cursor = move_cursor(ctx.window());
 				}
 				Event::WindowSize(_) => {
 					// Need to request full repaint to ensure everything draws correctly
 					ctx.request_paint();
 				}
-				Event::KeyDown(e) => {
-					if e.code == druid::Code::Backspace {
-						data.objects.retain(|object| !object.is_selected());
+				Event::AnimFrame(interval) => {
+					if data.animating {
+						data.time += *interval as f64 / 1e9;
+						ctx.request_anim_frame();
 					}
 				}
 				_ => (),
 			}
 		}
+		if affects_cursor {
+			self.update_cursor(ctx, data, env);
+		}
 		data.preview = data.tool.get_preview();
 	}
 
@@ -181,46 +604,18 @@ impl Widget<GraphicsData> for GraphicsWidget {
 		data: &GraphicsData,
 		_env: &druid::Env,
 	) {
-		let old_to_widget = old_data.get_trans_to_widget();
-		let to_widget = data.get_trans_to_widget();
-		if old_to_widget != to_widget {
-			ctx.request_paint();
-			return;
+		if data.animating && !old_data.animating {
+			ctx.request_anim_frame();
 		}
 
-		for (new, old) in data.objects.iter().zip(&old_data.objects) {
-			if new.same(old) {
-				ctx.request_paint_rect(to_widget.transform_rect_bbox(old.get_drawable().AABB()));
-				ctx.request_paint_rect(to_widget.transform_rect_bbox(new.get_drawable().AABB()));
-			}
-		}
-
-		match data.objects.len().cmp(&old_data.objects.len()) {
-			std::cmp::Ordering::Less => {
-				for obj in data.objects.iter().skip(old_data.objects.len()) {
-					ctx.request_paint_rect(
-						to_widget.transform_rect_bbox(obj.get_drawable().AABB()),
-					);
-				}
-			}
-			std::cmp::Ordering::Equal => (),
-			std::cmp::Ordering::Greater => {
-				for obj in old_data.objects.iter().skip(data.objects.len()) {
-					ctx.request_paint_rect(
-						to_widget.transform_rect_bbox(obj.get_drawable().AABB()),
-					);
+		match scene_diff::invalidation(old_data, data) {
+			Invalidation::Full => ctx.request_paint(),
+			Invalidation::Rects(rects) => {
+				for rect in rects {
+					ctx.request_paint_rect(rect);
 				}
 			}
 		}
-
-		if old_data.tool != data.tool {
-			if let Some(item) = old_data.tool.get_preview() {
-				ctx.request_paint_rect(to_widget.transform_rect_bbox(item.get_drawable().AABB()));
-			}
-			if let Some(item) = data.tool.get_preview() {
-				ctx.request_paint_rect(to_widget.transform_rect_bbox(item.get_drawable().AABB()));
-			}
-		}
 	}
 
 	fn layout(
@@ -237,6 +632,8 @@ impl Widget<GraphicsData> for GraphicsWidget {
 	}
 
 	fn paint(&mut self, ctx: &mut druid::PaintCtx, data: &GraphicsData, env: &druid::Env) {
+		let env = &env.clone().adding(ANIMATION_TIME, data.time);
+
 		// For some reason without this we end up clipping WAY out of bounds on full redraw
 		ctx.clip(self.port);
 
@@ -248,8 +645,8 @@ impl Widget<GraphicsData> for GraphicsWidget {
 
 		let mut redraw_needed = Vector::new();
 		for object in data.objects.iter() {
-			if !to_widget_space
-				.transform_rect_bbox(object.get_drawable().AABB())
+			if !object
+				.bounds_in(to_widget_space)
 				.intersect(ctx.region().bounding_box())
 				.is_empty()
 			{
@@ -257,7 +654,11 @@ impl Widget<GraphicsData> for GraphicsWidget {
 			}
 		}
 
-		ctx.clear(Color::WHITE);
+		ctx.clear(env.get(BACKGROUND_COLOR));
+
+		if data.overlays.grid {
+			canvas_overlays::paint_grid(ctx, self.port, to_widget_space, data.snap.grid_size);
+		}
 
 		ctx.save().unwrap();
 		for robj in redraw_needed {
@@ -265,6 +666,39 @@ impl Widget<GraphicsData> for GraphicsWidget {
 		}
 		data.tool.paint(ctx, env);
 		ctx.restore().unwrap();
+
+		// Everything else is drawn in widget space, over the top of the scene
+		ctx.with_save(|ctx| {
+			ctx.transform(to_widget_space.inverse());
+			if let Some(object) = self.hovered.and_then(|id| data.object(id)) {
+				let mut outline = object.collision_shape(env.get(COLLISION_TOLERANCE), data.time);
+				outline.apply_affine(to_widget_space);
+				ctx.stroke(outline, &HOVER_COLOR, HOVER_WIDTH);
+			}
+			data.tool.paint_overlay(ctx, to_widget_space, &data.objects);
+			if let Some(target) = data.snap_target {
+				target.paint_indicator(ctx, to_widget_space);
+			}
+			if data.overlays.origin {
+				canvas_overlays::paint_origin(ctx, to_widget_space);
+			}
+			if data.overlays.rulers {
+				canvas_overlays::paint_rulers(ctx, self.port, to_widget_space, data.snap.grid_size);
+			}
+		});
+	}
+}
+
+// The boolean operation a command asks for, if any
+fn boolean_op(command: &druid::Command) -> Option<BooleanOp> {
+	if command.is(UNION_SELECTED) {
+		Some(BooleanOp::Union)
+	} else if command.is(INTERSECT_SELECTED) {
+		Some(BooleanOp::Intersection)
+	} else if command.is(SUBTRACT_SELECTED) {
+		Some(BooleanOp::Difference)
+	} else {
+		None
 	}
 }
 
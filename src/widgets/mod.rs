@@ -1,3 +1,12 @@
+pub mod canvas_overlays;
+pub mod command_palette;
 pub mod compose_widgets;
+pub mod curve_editor;
 pub mod graphics_data;
 pub mod graphics_scene_widget;
+pub mod move_cursor;
+pub mod preferences_dialog;
+pub mod preset_menu;
+pub mod scene_diff;
+pub mod tool_preview;
+pub mod tooltip;
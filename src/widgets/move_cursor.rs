@@ -0,0 +1,72 @@
+//! The four way arrow shown over selected objects, which druid has no builtin cursor for.
+use druid::{
+	kurbo::{BezPath, ParamCurveNearest, Shape},
+	piet::ImageFormat,
+	Affine, Cursor, CursorDesc, ImageBuf, Point, WindowHandle,
+};
+
+// Width and height of the cursor image in pixels
+const SIZE: usize = 24;
+// Distance from the middle to the tip of each arrow
+const REACH: f64 = 10.5;
+// Half the thickness of each arrow's shaft
+const SHAFT: f64 = 1.5;
+const HEAD_LENGTH: f64 = 4.0;
+// Half the width of each arrow's head
+const HEAD_WIDTH: f64 = 4.5;
+// Width of the dark edge around the light arrows, so that they show up on any background
+const OUTLINE: f64 = 1.25;
+
+/// Make the move cursor for a window, None if the platform doesn't support custom cursors
+pub fn move_cursor(window: &WindowHandle) -> Option<Cursor> {
+	let arrows = arrows();
+	let middle = SIZE as f64 / 2.0;
+	let mut pixels = Vec::with_capacity(SIZE * SIZE * 4);
+	for y in 0..SIZE {
+		for x in 0..SIZE {
+			let p = Point::new(x as f64 + 0.5 - middle, y as f64 + 0.5 - middle);
+			let edge_distance = arrows
+				.segments()
+				.map(|segment| segment.nearest(p, 0.01).1)
+				.fold(f64::MAX, f64::min)
+				.sqrt();
+			let rgba = if arrows.winding(p) == 0 {
+				[0, 0, 0, 0]
+			} else if edge_distance < OUTLINE {
+				[0, 0, 0, 255]
+			} else {
+				[255, 255, 255, 255]
+			};
+			pixels.extend_from_slice(&rgba);
+		}
+	}
+	let image = ImageBuf::from_raw(pixels, ImageFormat::RgbaSeparate, SIZE, SIZE);
+	window.make_cursor(&CursorDesc::new(image, (middle, middle)))
+}
+
+// Arrows pointing right, down, left and up from the origin, joined into a single outline
+fn arrows() -> BezPath {
+	// The arrow pointing right, from where it leaves the arrow pointing up to where it meets the
+	// one pointing down. Turning it a quarter turn gives the next arrow round.
+	let arrow = [
+		Point::new(SHAFT, -SHAFT),
+		Point::new(REACH - HEAD_LENGTH, -SHAFT),
+		Point::new(REACH - HEAD_LENGTH, -HEAD_WIDTH),
+		Point::new(REACH, 0.0),
+		Point::new(REACH - HEAD_LENGTH, HEAD_WIDTH),
+		Point::new(REACH - HEAD_LENGTH, SHAFT),
+	];
+	let mut path = BezPath::new();
+	for quarter in 0..4 {
+		let turn = Affine::rotate(quarter as f64 * std::f64::consts::FRAC_PI_2);
+		for p in arrow {
+			if path.elements().is_empty() {
+				path.move_to(turn * p);
+			} else {
+				path.line_to(turn * p);
+			}
+		}
+	}
+	path.close_path();
+	path
+}
@@ -0,0 +1,134 @@
+use druid::{
+	widget::{Button, Checkbox, Flex, Label},
+	Color, Lens, LensExt, Widget, WidgetExt,
+};
+
+use super::{compose_widgets::Param, graphics_data::GraphicsData, tooltip::TooltipLayer};
+use crate::preferences::{
+	Preferences, COLLISION_TOLERANCE_RANGE, SELECTION_DASH_RANGE, SELECTION_GAP_RANGE,
+	SIMPLIFY_TOLERANCE_RANGE, WHEEL_ZOOM_SENSITIVITY_RANGE, ZOOM_STEP_RANGE,
+};
+
+/// Edits the preferences, which take effect as they change and are written out on save
+pub fn preferences_dialog() -> impl Widget<GraphicsData> {
+	let defaults = Preferences::default();
+	let dialog = Flex::column()
+		.with_child(color_setting(
+			"Background",
+			&defaults.background,
+			Preferences::background,
+		))
+		.with_child(color_setting(
+			"Selection",
+			&defaults.selection_color,
+			Preferences::selection_color,
+		))
+		.with_child(
+			Param::new(
+				"Selection Dash",
+				SELECTION_DASH_RANGE.0,
+				SELECTION_DASH_RANGE.1,
+				defaults.selection_dash,
+			)
+			.unit("px")
+			.slider(Preferences::selection_dash),
+		)
+		.with_child(
+			Param::new(
+				"Selection Gap",
+				SELECTION_GAP_RANGE.0,
+				SELECTION_GAP_RANGE.1,
+				defaults.selection_gap,
+			)
+			.unit("px")
+			.slider(Preferences::selection_gap),
+		)
+		.with_child(
+			Param::new(
+				"Collision Tolerance",
+				COLLISION_TOLERANCE_RANGE.0,
+				COLLISION_TOLERANCE_RANGE.1,
+				defaults.collision_tolerance,
+			)
+			.unit("px")
+			.log_scale()
+			.tooltip("How coarsely objects are flattened when testing what the selection touches")
+			.slider(Preferences::collision_tolerance),
+		)
+		.with_child(
+			Param::new(
+				"Zoom Step",
+				ZOOM_STEP_RANGE.0,
+				ZOOM_STEP_RANGE.1,
+				defaults.zoom_step,
+			)
+			.unit("×")
+			.tooltip("Zoom factor applied by each press of the zoom keys")
+			.slider(Preferences::zoom_step),
+		)
+		.with_child(
+			Param::new(
+				"Wheel Zoom",
+				WHEEL_ZOOM_SENSITIVITY_RANGE.0,
+				WHEEL_ZOOM_SENSITIVITY_RANGE.1,
+				defaults.wheel_zoom_sensitivity,
+			)
+			.log_scale()
+			.tooltip("How much each pixel of scrolling zooms")
+			.slider(Preferences::wheel_zoom_sensitivity),
+		)
+		.with_child(
+			Param::new(
+				"Path Simplification",
+				SIMPLIFY_TOLERANCE_RANGE.0,
+				SIMPLIFY_TOLERANCE_RANGE.1,
+				defaults.simplify_tolerance,
+			)
+			.unit("px")
+			.tooltip("How far converted and exported paths may stray, zero keeps every point")
+			.slider(Preferences::simplify_tolerance),
+		)
+		.with_child(Checkbox::new("Smooth Paths Into Curves").lens(Preferences::fit_curves))
+		.with_child(
+			Flex::row()
+				.with_child(
+					Button::new("Reset to Defaults").on_click(
+						|_ctx, data: &mut Preferences, _env| *data = Preferences::default(),
+					),
+				)
+				.with_child(
+					Button::new("Save").on_click(|_ctx, data: &mut Preferences, _env| data.save()),
+				),
+		)
+		.padding(8.0)
+		.lens(GraphicsData::preferences);
+	TooltipLayer::new(dialog)
+}
+
+// Red, green and blue sliders for a color
+fn color_setting<L: Lens<Preferences, Color> + Clone + 'static>(
+	name: &str,
+	default: &Color,
+	color: L,
+) -> impl Widget<Preferences> {
+	let mut col = Flex::column().with_child(Label::new(name));
+	for (channel, channel_name) in ["Red", "Green", "Blue"].iter().enumerate() {
+		col.add_child(
+			Param::new(*channel_name, 0.0, 1.0, rgba(default)[channel]).slider(color.clone().map(
+				move |c: &Color| rgba(c)[channel],
+				move |c: &mut Color, value| {
+					let mut components = rgba(c);
+					components[channel] = value;
+					let [r, g, b, a] = components;
+					*c = Color::rgba(r, g, b, a);
+				},
+			)),
+		);
+	}
+	col
+}
+
+fn rgba(color: &Color) -> [f64; 4] {
+	let (r, g, b, a) = color.as_rgba();
+	[r, g, b, a]
+}
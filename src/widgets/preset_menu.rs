@@ -0,0 +1,98 @@
+use druid::{
+	widget::{Button, Controller, Flex, Label, List, TextBox},
+	Env, Event, EventCtx, Widget, WidgetExt,
+};
+
+use super::graphics_data::GraphicsData;
+use crate::draw_tools::{
+	presets::{LinePreset, PresetLibrary, APPLY_PRESET, DELETE_PRESET, SAVE_PRESET},
+	Tool,
+};
+
+/// Lists the saved fractal line presets and lets the current settings be saved as a new one
+pub fn preset_menu() -> impl Widget<GraphicsData> {
+	Flex::column()
+		.with_child(Label::new(|data: &PresetLibrary, _env: &Env| {
+			match &data.active {
+				Some(name) => format!("Preset: {}", name),
+				None => "Presets".to_string(),
+			}
+		}))
+		.with_child(List::new(preset_row).lens(PresetLibrary::presets))
+		.with_child(
+			Flex::row()
+				.with_flex_child(
+					TextBox::new()
+						.with_placeholder("Preset name")
+						.lens(PresetLibrary::new_name)
+						.expand_width(),
+					1.0,
+				)
+				.with_child(Button::new("Save").on_click(
+					|ctx, _data: &mut PresetLibrary, _env| ctx.submit_command(SAVE_PRESET),
+				)),
+		)
+		.lens(GraphicsData::presets)
+		.controller(PresetController)
+}
+
+fn preset_row() -> impl Widget<LinePreset> {
+	Flex::row()
+		.with_flex_child(
+			Button::new(|preset: &LinePreset, _env: &Env| preset.name.clone())
+				.on_click(|ctx, preset: &mut LinePreset, _env| {
+					ctx.submit_command(APPLY_PRESET.with(preset.name.clone()))
+				})
+				.expand_width(),
+			1.0,
+		)
+		.with_child(
+			Button::new("×").on_click(|ctx, preset: &mut LinePreset, _env| {
+				ctx.submit_command(DELETE_PRESET.with(preset.name.clone()))
+			}),
+		)
+}
+
+// Carries out the preset commands, which need both the presets and the tool
+struct PresetController;
+
+impl<W: Widget<GraphicsData>> Controller<GraphicsData, W> for PresetController {
+	fn event(
+		&mut self,
+		child: &mut W,
+		ctx: &mut EventCtx,
+		event: &Event,
+		data: &mut GraphicsData,
+		env: &Env,
+	) {
+		let command = match event {
+			Event::Command(command) => command,
+			_ => return child.event(ctx, event, data, env),
+		};
+		if command.is(SAVE_PRESET) {
+			let name = data.presets.new_name.trim().to_string();
+			if let Tool::FractalLineTool(tool) = &data.tool {
+				if !name.is_empty() {
+					data.presets.insert(tool.preset(&name));
+					data.presets.active = Some(name);
+					data.presets.new_name.clear();
+					data.presets.save();
+				}
+			}
+		} else if let Some(name) = command.get(APPLY_PRESET) {
+			if let (Tool::FractalLineTool(tool), Some(preset)) =
+				(&mut data.tool, data.presets.get(name))
+			{
+				tool.apply_preset(preset);
+				data.presets.active = Some(name.clone());
+				data.presets.save();
+			}
+		} else if let Some(name) = command.get(DELETE_PRESET) {
+			data.presets.remove(name);
+			data.presets.save();
+		} else {
+			return child.event(ctx, event, data, env);
+		}
+		ctx.set_handled();
+	}
+}
@@ -0,0 +1,338 @@
+//! Works out which parts of the canvas need repainting when the scene changes.
+//!
+//! Objects are matched between the old and new scene by their id, and every object that
+//! was added, removed, changed or moved in the drawing order has both its old and new
+//! widget space bounds repainted. An object counts as moved in the drawing order only if it
+//! is outside the largest group of objects that kept their order relative to each other.
+use std::collections::{HashMap, HashSet};
+
+use druid::{im::Vector, Affine, Data, Rect};
+
+use super::graphics_data::GraphicsData;
+use crate::render_objects::{ObjectId, RenderObject};
+
+/// What of the canvas has to be repainted after an update
+#[derive(Debug, Clone, PartialEq)]
+pub enum Invalidation {
+	/// Everything, such as when the view moves
+	Full,
+	/// Only these widget space areas
+	Rects(Vec<Rect>),
+}
+
+/// How the objects in a scene differ between two versions of it
+#[derive(Debug, Clone, Default)]
+pub struct SceneDiff {
+	pub added: Vec<RenderObject>,
+	pub removed: Vec<RenderObject>,
+	/// Objects that are in both scenes but look different, as (old, new)
+	pub changed: Vec<(RenderObject, RenderObject)>,
+	/// Objects that look the same but are drawn in a different order relative to the others
+	pub reordered: Vec<RenderObject>,
+}
+
+impl SceneDiff {
+	pub fn between(old: &Vector<RenderObject>, new: &Vector<RenderObject>) -> Self {
+		let mut diff = Self::default();
+		if old.same(new) {
+			return diff;
+		}
+		let old_objects: HashMap<ObjectId, &RenderObject> =
+			old.iter().map(|obj| (obj.id, obj)).collect();
+		let new_ids: HashSet<ObjectId> = new.iter().map(|obj| obj.id).collect();
+
+		for obj in new.iter() {
+			match old_objects.get(&obj.id) {
+				Some(old_obj) if looks_same(old_obj, obj) => (),
+				Some(old_obj) => diff.changed.push(((*old_obj).clone(), obj.clone())),
+				None => diff.added.push(obj.clone()),
+			}
+		}
+		diff.removed = old
+			.iter()
+			.filter(|obj| !new_ids.contains(&obj.id))
+			.cloned()
+			.collect();
+
+		// The largest set of objects still drawn in the same order relative to each other stays
+		// put, and only the others now overlap their neighbours differently
+		let old_index: HashMap<ObjectId, usize> = old
+			.iter()
+			.filter(|obj| new_ids.contains(&obj.id))
+			.enumerate()
+			.map(|(i, obj)| (obj.id, i))
+			.collect();
+		let kept: Vec<&RenderObject> = new
+			.iter()
+			.filter(|obj| old_index.contains_key(&obj.id))
+			.collect();
+		let in_order = longest_increasing(
+			&kept
+				.iter()
+				.map(|obj| old_index[&obj.id])
+				.collect::<Vec<_>>(),
+		);
+		for (i, new_obj) in kept.into_iter().enumerate() {
+			if !in_order[i] {
+				diff.reordered.push(old_objects[&new_obj.id].clone());
+				diff.reordered.push(new_obj.clone());
+			}
+		}
+		diff
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.added.is_empty()
+			&& self.removed.is_empty()
+			&& self.changed.is_empty()
+			&& self.reordered.is_empty()
+	}
+
+	/// The widget space areas covering every difference
+	pub fn dirty_rects(&self, to_widget: Affine) -> Vec<Rect> {
+		self.added
+			.iter()
+			.chain(&self.removed)
+			.chain(self.changed.iter().flat_map(|(old, new)| vec![old, new]))
+			.chain(&self.reordered)
+			.map(|obj| object_rect(obj, to_widget))
+			.collect()
+	}
+}
+
+/// Everything that has to be repainted to go from showing old to showing new
+pub fn invalidation(old: &GraphicsData, new: &GraphicsData) -> Invalidation {
+	let to_widget = new.get_trans_to_widget();
+	if old.get_trans_to_widget() != to_widget
+		|| !old.overlays.same(&new.overlays)
+		|| !old.preferences.same(&new.preferences)
+	{
+		return Invalidation::Full;
+	}
+
+	let mut rects = SceneDiff::between(&old.objects, &new.objects).dirty_rects(to_widget);
+
+	if !old.time.same(&new.time) {
+		rects.extend(
+			new.objects
+				.iter()
+				.filter(|obj| obj.drawable.is_animated())
+				.map(|obj| object_rect(obj, to_widget)),
+		);
+	}
+
+	// The tool makes a new preview object each time, so only how it looks is compared
+	let preview_changed = match (&old.preview, &new.preview) {
+		(Some(old_preview), Some(new_preview)) => !looks_same(old_preview, new_preview),
+		(None, None) => false,
+		_ => true,
+	};
+	if preview_changed {
+		rects.extend(
+			old.preview
+				.iter()
+				.chain(new.preview.iter())
+				.map(|obj| object_rect(obj, to_widget)),
+		);
+	}
+
+	if old.snap_target != new.snap_target {
+		rects.extend(
+			old.snap_target
+				.iter()
+				.chain(new.snap_target.iter())
+				.map(|target| target.indicator_rect(to_widget)),
+		);
+	}
+
+	if old.tool != new.tool || !old.objects.same(&new.objects) {
+		rects.extend(old.tool.overlay_rects(to_widget, &old.objects));
+		rects.extend(new.tool.overlay_rects(to_widget, &new.objects));
+	}
+
+	Invalidation::Rects(rects)
+}
+
+// Which of the values make up a longest strictly increasing subsequence of them, found by
+// patience sorting in O(n log n) as scenes can hold thousands of objects
+fn longest_increasing(values: &[usize]) -> Vec<bool> {
+	// The index of the last value of the best subsequence found so far of each length
+	let mut tails: Vec<usize> = Vec::new();
+	// The index of the value before each value in the best subsequence ending with it
+	let mut previous: Vec<Option<usize>> = vec![None; values.len()];
+	for (i, &value) in values.iter().enumerate() {
+		let length = tails.partition_point(|&tail| values[tail] < value);
+		previous[i] = length.checked_sub(1).map(|before| tails[before]);
+		if length == tails.len() {
+			tails.push(i);
+		} else {
+			tails[length] = i;
+		}
+	}
+	let mut in_sequence = vec![false; values.len()];
+	let mut current = tails.last().copied();
+	while let Some(i) = current {
+		in_sequence[i] = true;
+		current = previous[i];
+	}
+	in_sequence
+}
+
+// The widget space area an object is drawn in
+fn object_rect(obj: &RenderObject, to_widget: Affine) -> Rect {
+	obj.bounds_in(to_widget)
+}
+
+// Whether two objects would be drawn identically, whatever their ids
+fn looks_same(a: &RenderObject, b: &RenderObject) -> bool {
+	a.transform == b.transform && a.selected == b.selected && a.drawable.same(&b.drawable)
+}
+
+#[cfg(test)]
+mod tests {
+	use druid::kurbo::BezPath;
+
+	use super::*;
+	use crate::render_objects::{Drawable, PathShape};
+
+	fn line(x: f64) -> RenderObject {
+		let mut path = BezPath::new();
+		path.move_to((x, 0.0));
+		path.line_to((x + 10.0, 10.0));
+		RenderObject::new(Drawable::PathShape(PathShape::stroked(path, 1.0)))
+	}
+
+	fn scene(count: usize) -> Vector<RenderObject> {
+		(0..count).map(|i| line(i as f64 * 20.0)).collect()
+	}
+
+	fn ids(objects: &[RenderObject]) -> Vec<ObjectId> {
+		objects.iter().map(|obj| obj.id).collect()
+	}
+
+	fn rect(obj: &RenderObject) -> Rect {
+		object_rect(obj, Affine::default())
+	}
+
+	#[test]
+	fn unchanged_scene_has_no_dirty_rects() {
+		let old = scene(5);
+		// A separate copy, so that the diff can't take the shortcut for shared vectors
+		let new: Vector<RenderObject> = old.iter().cloned().collect();
+		let diff = SceneDiff::between(&old, &new);
+		assert!(diff.is_empty());
+		assert!(diff.dirty_rects(Affine::default()).is_empty());
+	}
+
+	#[test]
+	fn object_added_in_the_middle() {
+		let old = scene(5);
+		let mut new = old.clone();
+		let added = line(200.0);
+		new.insert(2, added.clone());
+		let diff = SceneDiff::between(&old, &new);
+		assert_eq!(ids(&diff.added), vec![added.id]);
+		assert!(diff.removed.is_empty() && diff.changed.is_empty() && diff.reordered.is_empty());
+		assert_eq!(diff.dirty_rects(Affine::default()), vec![rect(&added)]);
+	}
+
+	#[test]
+	fn object_removed_from_the_middle() {
+		let old = scene(5);
+		let mut new = old.clone();
+		let removed = new.remove(2);
+		let diff = SceneDiff::between(&old, &new);
+		assert_eq!(ids(&diff.removed), vec![removed.id]);
+		assert!(diff.added.is_empty() && diff.changed.is_empty() && diff.reordered.is_empty());
+		assert_eq!(diff.dirty_rects(Affine::default()), vec![rect(&removed)]);
+	}
+
+	#[test]
+	fn object_changed_in_the_middle() {
+		let old = scene(5);
+		let mut new = old.clone();
+		let moved = new.get_mut(2).unwrap();
+		moved.transform = Affine::translate((0.0, 50.0)) * moved.transform;
+		let moved = moved.clone();
+		let diff = SceneDiff::between(&old, &new);
+		assert_eq!(diff.changed.len(), 1);
+		assert_eq!(diff.changed[0].1.id, moved.id);
+		assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.reordered.is_empty());
+		assert_eq!(
+			diff.dirty_rects(Affine::default()),
+			vec![rect(&old[2]), rect(&moved)]
+		);
+	}
+
+	#[test]
+	fn selection_toggle_is_a_change() {
+		let old = scene(3);
+		let mut new = old.clone();
+		new.get_mut(1).unwrap().select();
+		let diff = SceneDiff::between(&old, &new);
+		assert_eq!(diff.changed.len(), 1);
+		assert_eq!(diff.changed[0].0.id, old[1].id);
+		assert_eq!(
+			diff.dirty_rects(Affine::default()),
+			vec![rect(&old[1]), rect(&old[1])]
+		);
+	}
+
+	#[test]
+	fn reorder_marks_only_the_moved_object() {
+		let old = scene(5);
+		let mut new = old.clone();
+		// Bringing the bottom object to the top leaves the others in the same order
+		let bottom = new.pop_front().unwrap();
+		new.push_back(bottom.clone());
+		let diff = SceneDiff::between(&old, &new);
+		assert_eq!(ids(&diff.reordered), vec![bottom.id, bottom.id]);
+		assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty());
+		assert_eq!(
+			diff.dirty_rects(Affine::default()),
+			vec![rect(&bottom), rect(&bottom)]
+		);
+	}
+
+	#[test]
+	fn swapping_neighbours_marks_one_of_them() {
+		let old = scene(4);
+		let mut new = old.clone();
+		new.swap(1, 2);
+		let diff = SceneDiff::between(&old, &new);
+		let reordered = ids(&diff.reordered);
+		assert_eq!(reordered.len(), 2);
+		assert_eq!(reordered[0], reordered[1]);
+		assert!([old[1].id, old[2].id].contains(&reordered[0]));
+	}
+
+	#[test]
+	fn preview_change_repaints_old_and_new_preview() {
+		let mut old = GraphicsData::new();
+		old.objects = scene(3);
+		let mut new = old.clone();
+		new.preview = Some(line(100.0));
+		assert_eq!(
+			invalidation(&old, &new),
+			Invalidation::Rects(vec![rect(new.preview.as_ref().unwrap())])
+		);
+
+		let mut newer = new.clone();
+		newer.preview = Some(line(300.0));
+		assert_eq!(
+			invalidation(&new, &newer),
+			Invalidation::Rects(vec![
+				rect(new.preview.as_ref().unwrap()),
+				rect(newer.preview.as_ref().unwrap())
+			])
+		);
+	}
+
+	#[test]
+	fn unchanged_data_repaints_nothing() {
+		let mut old = GraphicsData::new();
+		old.objects = scene(3);
+		let new = old.clone();
+		assert_eq!(invalidation(&old, &new), Invalidation::Rects(Vec::new()));
+	}
+}
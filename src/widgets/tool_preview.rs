@@ -0,0 +1,136 @@
+use druid::{
+	im::Vector, widget::Controller, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx,
+	LifeCycle, LifeCycleCtx, PaintCtx, Point, RenderContext, Size, UpdateCtx, Widget,
+};
+
+use super::graphics_data::GraphicsData;
+use crate::{
+	draw_tools::{FractalLineTool, Tool},
+	preferences::BACKGROUND_COLOR,
+	render_objects::RenderObject,
+};
+
+// A fixed seed so that the swatch only changes when the settings do
+const SWATCH_SEED: u32 = 0x5eed;
+// Space left at each end of the swatch line
+const SWATCH_MARGIN: f64 = 16.0;
+
+/// A sample line drawn with the fractal line tool's current settings
+pub struct LineSwatch {
+	size: Size,
+}
+
+impl LineSwatch {
+	pub fn new() -> Self {
+		Self {
+			size: Size::new(240.0, 72.0),
+		}
+	}
+}
+
+impl Default for LineSwatch {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Widget<FractalLineTool> for LineSwatch {
+	fn event(
+		&mut self,
+		_ctx: &mut EventCtx,
+		_event: &Event,
+		_data: &mut FractalLineTool,
+		_env: &Env,
+	) {
+	}
+
+	fn lifecycle(
+		&mut self,
+		_ctx: &mut LifeCycleCtx,
+		_event: &LifeCycle,
+		_data: &FractalLineTool,
+		_env: &Env,
+	) {
+	}
+
+	fn update(
+		&mut self,
+		ctx: &mut UpdateCtx,
+		old_data: &FractalLineTool,
+		data: &FractalLineTool,
+		_env: &Env,
+	) {
+		if !old_data.same(data) {
+			ctx.request_paint();
+		}
+	}
+
+	fn layout(
+		&mut self,
+		_ctx: &mut LayoutCtx,
+		bc: &BoxConstraints,
+		_data: &FractalLineTool,
+		_env: &Env,
+	) -> Size {
+		bc.constrain(self.size)
+	}
+
+	fn paint(&mut self, ctx: &mut PaintCtx, data: &FractalLineTool, env: &Env) {
+		let bounds = ctx.size().to_rect();
+		let y = bounds.center().y;
+		let object = data.build_object(data.build_line(
+			Point::new(bounds.x0 + SWATCH_MARGIN, y),
+			Point::new(bounds.x1 - SWATCH_MARGIN, y),
+			SWATCH_SEED,
+		));
+		ctx.with_save(|ctx| {
+			ctx.clip(bounds);
+			ctx.fill(bounds, &env.get(BACKGROUND_COLOR));
+			object.paint(ctx, env);
+		});
+	}
+}
+
+/// Restyles the selected objects as the fractal line tool's settings change, if enabled
+#[derive(Default)]
+pub struct ApplyToSelection {
+	// The objects when a drag started, so that the whole drag is undone at once
+	gesture_start: Option<Vector<RenderObject>>,
+}
+
+impl<W: Widget<GraphicsData>> Controller<GraphicsData, W> for ApplyToSelection {
+	fn event(
+		&mut self,
+		child: &mut W,
+		ctx: &mut EventCtx,
+		event: &Event,
+		data: &mut GraphicsData,
+		env: &Env,
+	) {
+		if let Event::MouseDown(_) = event {
+			self.gesture_start = Some(data.objects.clone());
+		}
+		let old_tool = data.tool;
+		let before = data.objects.clone();
+		child.event(ctx, event, data, env);
+
+		if data.apply_to_selection && !old_tool.same(&data.tool) {
+			if let Tool::FractalLineTool(tool) = data.tool {
+				for object in data.objects.iter_mut().filter(|o| o.is_selected()) {
+					if let Some(restyled) = tool.restyle(object) {
+						*object = restyled;
+					}
+				}
+				// Changes that aren't part of a drag, like typed values, are undone one at a time
+				if self.gesture_start.is_none() {
+					data.checkpoint(before);
+				}
+			}
+		}
+		if let Event::MouseUp(_) = event {
+			if let Some(start) = self.gesture_start.take() {
+				data.checkpoint(start);
+			}
+		}
+	}
+}
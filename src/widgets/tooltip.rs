@@ -0,0 +1,181 @@
+use std::time::Duration;
+
+use druid::{
+	theme,
+	widget::{prelude::*, Controller},
+	Point, Rect, Selector, TextLayout, TimerToken, Vec2, WidgetPod,
+};
+
+// How long the pointer has to rest on a widget before its tooltip is shown
+const TOOLTIP_DELAY: Duration = Duration::from_millis(600);
+// Offset of the tooltip from the pointer so that it isn't covered by the cursor
+const TOOLTIP_OFFSET: Vec2 = Vec2::new(12.0, 16.0);
+// Space between the text and the edge of the tooltip
+const TOOLTIP_PADDING: f64 = 4.0;
+
+/// Sent to the window by a widget's Tooltip, with the text and the window space pointer position
+pub const SHOW_TOOLTIP: Selector<(String, Point)> = Selector::new("lipuma.show-tooltip");
+/// Sent to the window to remove any tooltip it is showing
+pub const HIDE_TOOLTIP: Selector = Selector::new("lipuma.hide-tooltip");
+
+enum TooltipState {
+	Hidden,
+	// The pointer is resting at a window space position, waiting for the timer
+	Waiting(TimerToken, Point),
+	Showing,
+}
+
+/// Asks the window's TooltipLayer to show some text next to the pointer after it rests on the child
+pub struct Tooltip<T> {
+	text: Box<dyn Fn(&T, &Env) -> String>,
+	state: TooltipState,
+}
+
+impl<T> Tooltip<T> {
+	pub fn new(text: impl Fn(&T, &Env) -> String + 'static) -> Self {
+		Self {
+			text: Box::new(text),
+			state: TooltipState::Hidden,
+		}
+	}
+
+	fn hide(&mut self, ctx: &mut EventCtx) {
+		if let TooltipState::Showing = self.state {
+			ctx.submit_command(HIDE_TOOLTIP);
+		}
+		self.state = TooltipState::Hidden;
+	}
+}
+
+impl<T: Data, W: Widget<T>> Controller<T, W> for Tooltip<T> {
+	fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+		match event {
+			Event::MouseMove(e) if ctx.is_hot() => {
+				if !matches!(self.state, TooltipState::Showing) {
+					self.state =
+						TooltipState::Waiting(ctx.request_timer(TOOLTIP_DELAY), e.window_pos);
+				}
+			}
+			Event::MouseDown(_) | Event::Wheel(_) => self.hide(ctx),
+			Event::Timer(token) => {
+				if let TooltipState::Waiting(waiting, window_pos) = self.state {
+					let text = (self.text)(data, env);
+					// Widgets without anything to say don't get an empty tooltip
+					if *token == waiting && text.is_empty() {
+						self.state = TooltipState::Hidden;
+					} else if *token == waiting {
+						ctx.submit_command(SHOW_TOOLTIP.with((text, window_pos)));
+						self.state = TooltipState::Showing;
+						ctx.set_handled();
+						return;
+					}
+				}
+			}
+			_ => (),
+		}
+		child.event(ctx, event, data, env);
+	}
+
+	fn lifecycle(
+		&mut self,
+		child: &mut W,
+		ctx: &mut LifeCycleCtx,
+		event: &LifeCycle,
+		data: &T,
+		env: &Env,
+	) {
+		if let LifeCycle::HotChanged(false) = event {
+			if let TooltipState::Showing = self.state {
+				ctx.submit_command(HIDE_TOOLTIP);
+			}
+			self.state = TooltipState::Hidden;
+		}
+		child.lifecycle(ctx, event, data, env)
+	}
+}
+
+/// Paints tooltips over the rest of the window, it is expected to be the window's root widget so
+/// that a tooltip can cover any other widget
+pub struct TooltipLayer<T> {
+	child: WidgetPod<T, Box<dyn Widget<T>>>,
+	text: TextLayout<String>,
+	// The area the tooltip covers while it is showing
+	shown: Option<Rect>,
+}
+
+impl<T: Data> TooltipLayer<T> {
+	pub fn new(child: impl Widget<T> + 'static) -> Self {
+		Self {
+			child: WidgetPod::new(child).boxed(),
+			text: TextLayout::new(),
+			shown: None,
+		}
+	}
+
+	fn hide(&mut self, ctx: &mut EventCtx) {
+		if let Some(rect) = self.shown.take() {
+			ctx.request_paint_rect(rect);
+		}
+	}
+}
+
+impl<T: Data> Widget<T> for TooltipLayer<T> {
+	fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+		if let Event::Command(command) = event {
+			if let Some((text, pointer)) = command.get(SHOW_TOOLTIP) {
+				self.hide(ctx);
+				self.text.set_text(text.clone());
+				self.text.rebuild_if_needed(ctx.text(), env);
+				let text_size = self.text.size();
+				let size = Size::new(
+					text_size.width + TOOLTIP_PADDING * 2.0,
+					text_size.height + TOOLTIP_PADDING * 2.0,
+				);
+				// Kept inside the window, as nothing can be painted beyond it
+				let window = ctx.size();
+				let origin = *pointer + TOOLTIP_OFFSET;
+				let origin = Point::new(
+					origin.x.min(window.width - size.width).max(0.0),
+					origin.y.min(window.height - size.height).max(0.0),
+				);
+				let rect = Rect::from_origin_size(origin, size);
+				ctx.request_paint_rect(rect);
+				self.shown = Some(rect);
+				ctx.set_handled();
+				return;
+			}
+			if command.is(HIDE_TOOLTIP) {
+				self.hide(ctx);
+				ctx.set_handled();
+				return;
+			}
+		}
+		self.child.event(ctx, event, data, env);
+	}
+
+	fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+		self.child.lifecycle(ctx, event, data, env);
+	}
+
+	fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+		self.child.update(ctx, data, env);
+	}
+
+	fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+		let size = self.child.layout(ctx, bc, data, env);
+		self.child.set_origin(ctx, data, env, Point::ORIGIN);
+		size
+	}
+
+	fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+		self.child.paint(ctx, data, env);
+		if let Some(rect) = self.shown {
+			ctx.fill(rect, &env.get(theme::BACKGROUND_LIGHT));
+			ctx.stroke(rect.inset(-0.5), &env.get(theme::BORDER_DARK), 1.0);
+			self.text.draw(
+				ctx,
+				rect.origin() + Vec2::new(TOOLTIP_PADDING, TOOLTIP_PADDING),
+			);
+		}
+	}
+}